@@ -0,0 +1,62 @@
+use commander::executor::command::{ArgParserCommand, Command, NaturalCommand};
+use commander::executor::context::{CommandContext, Value};
+use commander::executor::{CommandChildContainer, Executor};
+use commander::protocol::Parser;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn level(depth: usize, index: usize) -> anyhow::Result<Command<()>> {
+    let mut value = Command::ArgParser(ArgParserCommand::executable(
+        Box::new(|_| Ok(())),
+        format!("value_{}", index),
+        Parser::Integer { bits: 0u8.into(), min: None, max: None },
+        Box::new(|mut context| {
+            let value = match context.args().first() {
+                Some(arg) => Value::Integer(arg.parse()?),
+                None => anyhow::bail!("Expected an integer."),
+            };
+            context.trim_top();
+            Ok((value, context))
+        }),
+    ));
+    if index + 1 < depth {
+        value.child(format!("level_{}", index + 1), level(depth, index + 1)?)?;
+    }
+    let mut literal = Command::Natural(NaturalCommand::default());
+    literal.child(format!("value_{}", index), value)?;
+    Ok(literal)
+}
+
+/// Executor with alternating literal and integer argument levels, plus the input reaching the
+/// deepest node.
+fn deep_tree(depth: usize) -> anyhow::Result<(Executor<()>, String)> {
+    let mut executor = Executor::new();
+    executor.child("level_0", level(depth, 0)?)?;
+    let input = (0..depth).map(|index| format!("level_{} {}", index, index)).collect::<Vec<String>>().join(" ");
+    Ok((executor, input))
+}
+
+fn dispatch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dispatch");
+    for depth in [4, 16, 64] {
+        let (executor, input) = deep_tree(depth).expect("The benchmark tree should build.");
+        let tokens = input.split(' ').collect::<Vec<&str>>();
+        // Baseline: one owned `String` per token for every dispatch, as before the input was shared.
+        group.bench_with_input(BenchmarkId::new("owned", depth), &tokens, |b, tokens| {
+            b.iter(|| executor.execute_context(CommandContext::new((), black_box(tokens).iter().map(|token| token.to_string()).collect())))
+        });
+        group.bench_with_input(BenchmarkId::new("tokens", depth), &tokens, |b, tokens| {
+            b.iter(|| executor.execute_context(CommandContext::create((), black_box(tokens.clone()))))
+        });
+        group.bench_with_input(BenchmarkId::new("input", depth), &input, |b, input| {
+            b.iter(|| executor.execute_context(CommandContext::from_input((), black_box(input))))
+        });
+        let results = executor.parse(CommandContext::from_input((), &input)).expect("The benchmark input should parse.");
+        group.bench_with_input(BenchmarkId::new("parsed", depth), &results, |b, results| {
+            b.iter(|| executor.execute(black_box(results), ()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
@@ -25,14 +25,12 @@ fn main() -> anyhow::Result<()> {
 
             arg_parser(
                 parser = commander::protocol::Parser::IntRange;
-                |(mut context)| {
-                    if let Some(arg) = context.args().first() {
-                        let value = Value::Integer(arg.parse()?);
-                        context.trim_top();
-                        Ok((value, context))
-                    } else {
-                        anyhow::bail!("Cannot understand integer argument.");
-                    }
+                |(context)| {
+                    let (bounds, context) = commander::arguments::parse_with_reader(
+                        context,
+                        commander::arguments::range::MinMaxBounds::<i32>::read,
+                    )?;
+                    Ok((Value::IntRange(bounds), context))
                 }
             )
 
@@ -61,7 +59,7 @@ fn main() -> anyhow::Result<()> {
     ))?;
     local_executor.execute_context(CommandContext::create(
         "Some Sender",
-        vec!["command_a", "126", "sub_2"],
+        vec!["command_a", "120..130", "sub_2"],
     ))?;
 
     Ok(())
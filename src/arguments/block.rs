@@ -0,0 +1,334 @@
+use crate::arguments::nbt::{read_compound, NbtCompound};
+use crate::arguments::reader::StringReader;
+use crate::arguments::reader_parser;
+use crate::executor::command::{ParserFunction, SuggestionFunction};
+use crate::executor::context::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Supplies the blocks, block tags and block state properties known to the host.
+pub trait BlockRegistry: Send + Sync {
+    fn blocks(&self) -> Vec<String>;
+
+    fn block_tags(&self) -> Vec<String>;
+
+    /// Property names of a block, e.g. `facing` and `half` for `minecraft:oak_stairs`.
+    fn properties(&self, block: &str) -> Vec<String>;
+
+    /// Values a property of a block accepts.
+    fn values(&self, block: &str, property: &str) -> Vec<String>;
+}
+
+/// Supplies the items and item tags known to the host.
+pub trait ItemRegistry: Send + Sync {
+    fn items(&self) -> Vec<String>;
+
+    fn item_tags(&self) -> Vec<String>;
+}
+
+/// Either a concrete resource or a `#` prefixed tag of resources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceTarget {
+    Id(String),
+    Tag(String),
+}
+
+impl ResourceTarget {
+    pub fn location(&self) -> &str {
+        match self {
+            ResourceTarget::Id(location) | ResourceTarget::Tag(location) => location,
+        }
+    }
+
+    pub fn is_tag(&self) -> bool {
+        matches!(self, ResourceTarget::Tag(_))
+    }
+}
+
+/// Parsed `minecraft:block_state` or `minecraft:block_predicate` argument, e.g.
+/// `minecraft:oak_stairs[facing=north]{Lock:""}` or `#minecraft:logs[axis=y]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockState {
+    target: ResourceTarget,
+    properties: BTreeMap<String, String>,
+    nbt: Option<NbtCompound>,
+}
+
+impl BlockState {
+    pub fn target(&self) -> &ResourceTarget {
+        &self.target
+    }
+
+    pub fn properties(&self) -> &BTreeMap<String, String> {
+        &self.properties
+    }
+
+    pub fn nbt(&self) -> Option<&NbtCompound> {
+        self.nbt.as_ref()
+    }
+}
+
+/// Parsed `minecraft:item_stack` or `minecraft:item_predicate` argument, e.g. `diamond_sword{Damage:3}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    target: ResourceTarget,
+    nbt: Option<NbtCompound>,
+}
+
+impl ItemStack {
+    pub fn target(&self) -> &ResourceTarget {
+        &self.target
+    }
+
+    pub fn nbt(&self) -> Option<&NbtCompound> {
+        self.nbt.as_ref()
+    }
+}
+
+/// Adds the `minecraft` namespace to locations which don't specify one.
+fn normalize(location: &str) -> String {
+    if location.contains(':') {
+        location.to_string()
+    } else {
+        format!("minecraft:{}", location)
+    }
+}
+
+fn read_target(reader: &mut StringReader, allow_tags: bool, kind: &str) -> anyhow::Result<ResourceTarget> {
+    if reader.peek() == Some('#') {
+        if !allow_tags {
+            return Err(reader.error(format!("Tags aren't allowed here, only actual {}s", kind)));
+        }
+        reader.skip();
+        return Ok(ResourceTarget::Tag(normalize(reader.read_resource_location()?)));
+    }
+    Ok(ResourceTarget::Id(normalize(reader.read_resource_location()?)))
+}
+
+fn validate_target(reader: &mut StringReader, start: usize, target: &ResourceTarget, known: Vec<String>, kind: &str) -> anyhow::Result<()> {
+    if !known.iter().any(|location| normalize(location) == target.location()) {
+        reader.set_cursor(start);
+        let kind = if target.is_tag() { format!("{} tag", kind) } else { kind.to_string() };
+        return Err(reader.error(format!("Unknown {} '{}'", kind, target.location())));
+    }
+    Ok(())
+}
+
+fn read_properties(reader: &mut StringReader, target: &ResourceTarget, registry: Option<&dyn BlockRegistry>) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut properties = BTreeMap::new();
+    reader.expect('[')?;
+    reader.skip_whitespace();
+    while reader.can_read() && reader.peek() != Some(']') {
+        let start = reader.cursor();
+        let property = reader.read_string()?;
+        let block = target.location();
+        let known = match (registry, target) {
+            (Some(registry), ResourceTarget::Id(_)) => Some(registry.properties(block)),
+            _ => None,
+        };
+        if property.is_empty() || known.as_ref().is_some_and(|known| !known.contains(&property)) {
+            reader.set_cursor(start);
+            return Err(reader.error(format!("Block {} does not accept '{}' property", block, property)));
+        }
+        if properties.contains_key(&property) {
+            reader.set_cursor(start);
+            return Err(reader.error(format!("Property '{}' can only be set once for block {}", property, block)));
+        }
+        reader.skip_whitespace();
+        if reader.peek() != Some('=') {
+            return Err(reader.error(format!("Expected value for property '{}' on block {}", property, block)));
+        }
+        reader.skip();
+        reader.skip_whitespace();
+        let value_start = reader.cursor();
+        let value = reader.read_string()?;
+        if let (Some(registry), Some(_)) = (registry, &known) {
+            if !registry.values(block, &property).contains(&value) {
+                reader.set_cursor(value_start);
+                return Err(reader.error(format!("Block {} does not accept '{}' for {} property", block, value, property)));
+            }
+        }
+        properties.insert(property, value);
+        reader.skip_whitespace();
+        if reader.peek() != Some(',') {
+            break;
+        }
+        reader.skip();
+        reader.skip_whitespace();
+    }
+    if reader.peek() != Some(']') {
+        return Err(reader.error("Expected closing ] for block state properties"));
+    }
+    reader.skip();
+    Ok(properties)
+}
+
+pub fn read_block_state(reader: &mut StringReader, registry: Option<&dyn BlockRegistry>, allow_tags: bool) -> anyhow::Result<BlockState> {
+    let start = reader.cursor();
+    let target = read_target(reader, allow_tags, "block")?;
+    if let Some(registry) = registry {
+        let known = if target.is_tag() { registry.block_tags() } else { registry.blocks() };
+        validate_target(reader, start, &target, known, "block")?;
+    }
+    let properties = if reader.peek() == Some('[') { read_properties(reader, &target, registry)? } else { BTreeMap::new() };
+    let nbt = if reader.peek() == Some('{') { Some(read_compound(reader)?) } else { None };
+    Ok(BlockState { target, properties, nbt })
+}
+
+pub fn read_item_stack(reader: &mut StringReader, registry: Option<&dyn ItemRegistry>, allow_tags: bool) -> anyhow::Result<ItemStack> {
+    let start = reader.cursor();
+    let target = read_target(reader, allow_tags, "item")?;
+    if let Some(registry) = registry {
+        let known = if target.is_tag() { registry.item_tags() } else { registry.items() };
+        validate_target(reader, start, &target, known, "item")?;
+    }
+    let nbt = if reader.peek() == Some('{') { Some(read_compound(reader)?) } else { None };
+    Ok(ItemStack { target, nbt })
+}
+
+/// Suggests resources matching a partial location, with or without the `minecraft` namespace.
+fn suggest_resources(partial: &str, ids: Vec<String>, tags: Option<Vec<String>>) -> Vec<String> {
+    let tags = tags.unwrap_or_default().into_iter().map(|tag| format!("#{}", normalize(&tag)));
+    let mut suggestions = ids
+        .iter()
+        .map(|id| normalize(id))
+        .chain(tags)
+        .filter(|candidate| {
+            let short = candidate.replacen("minecraft:", "", 1);
+            candidate.starts_with(partial) || short.starts_with(partial)
+        })
+        .collect::<Vec<String>>();
+    suggestions.sort();
+    suggestions.dedup();
+    suggestions
+}
+
+/// Suggests block ids, tags, property names and property values for a partial block argument.
+pub fn suggest_block(partial: &str, registry: &dyn BlockRegistry, allow_tags: bool) -> Vec<String> {
+    let open = match partial.find('[') {
+        Some(open) => open,
+        None => return suggest_resources(partial, registry.blocks(), allow_tags.then(|| registry.block_tags())),
+    };
+    if partial[open..].contains(']') || partial.starts_with('#') {
+        return Vec::new();
+    }
+    let block = normalize(&partial[..open]);
+    let (prefix, segment) = match partial.rfind(',') {
+        Some(comma) => partial.split_at(comma + 1),
+        None => partial.split_at(open + 1),
+    };
+    let mut suggestions = match segment.split_once('=') {
+        Some((property, value)) => registry
+            .values(&block, property.trim())
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(value.trim()))
+            .map(|candidate| format!("{}{}={}", prefix, property, candidate))
+            .collect::<Vec<String>>(),
+        None => {
+            let used = partial[open + 1..prefix.len().max(open + 1)]
+                .split(',')
+                .filter_map(|entry| entry.split_once('=').map(|(property, _)| property.trim().to_string()))
+                .collect::<Vec<String>>();
+            registry
+                .properties(&block)
+                .into_iter()
+                .filter(|property| property.starts_with(segment.trim()) && !used.contains(property))
+                .map(|property| format!("{}{}=", prefix, property))
+                .collect()
+        }
+    };
+    suggestions.sort();
+    suggestions
+}
+
+pub fn block_state_parser<T>(registry: Option<Arc<dyn BlockRegistry>>) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::Block(read_block_state(reader, registry.as_deref(), false)?)))
+}
+
+pub fn block_predicate_parser<T>(registry: Option<Arc<dyn BlockRegistry>>) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::Block(read_block_state(reader, registry.as_deref(), true)?)))
+}
+
+pub fn item_stack_parser<T>(registry: Option<Arc<dyn ItemRegistry>>) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::Item(read_item_stack(reader, registry.as_deref(), false)?)))
+}
+
+pub fn item_predicate_parser<T>(registry: Option<Arc<dyn ItemRegistry>>) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::Item(read_item_stack(reader, registry.as_deref(), true)?)))
+}
+
+pub fn block_suggestions<T>(registry: Arc<dyn BlockRegistry>, allow_tags: bool) -> SuggestionFunction<T> {
+    Box::new(move |_, partial| suggest_block(partial, registry.as_ref(), allow_tags))
+}
+
+pub fn item_suggestions<T>(registry: Arc<dyn ItemRegistry>, allow_tags: bool) -> SuggestionFunction<T> {
+    Box::new(move |_, partial| {
+        if partial.contains('{') {
+            return Vec::new();
+        }
+        suggest_resources(partial, registry.items(), allow_tags.then(|| registry.item_tags()))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::block::{read_block_state, read_item_stack, suggest_block, BlockRegistry, ResourceTarget};
+    use crate::arguments::nbt::NbtTag;
+    use crate::arguments::reader::StringReader;
+
+    struct Blocks;
+
+    impl BlockRegistry for Blocks {
+        fn blocks(&self) -> Vec<String> {
+            vec!["minecraft:oak_stairs".into(), "minecraft:stone".into()]
+        }
+
+        fn block_tags(&self) -> Vec<String> {
+            vec!["minecraft:logs".into()]
+        }
+
+        fn properties(&self, block: &str) -> Vec<String> {
+            match block {
+                "minecraft:oak_stairs" => vec!["facing".into(), "half".into()],
+                _ => Vec::new(),
+            }
+        }
+
+        fn values(&self, _: &str, property: &str) -> Vec<String> {
+            match property {
+                "facing" => vec!["east".into(), "north".into(), "south".into(), "west".into()],
+                "half" => vec!["bottom".into(), "top".into()],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_block_state() -> anyhow::Result<()> {
+        let state = read_block_state(&mut StringReader::new("oak_stairs[facing=north]{Lock:\"key\"}"), Some(&Blocks), false)?;
+        assert_eq!(*state.target(), ResourceTarget::Id("minecraft:oak_stairs".into()));
+        assert_eq!(state.properties().get("facing").map(String::as_str), Some("north"));
+        assert_eq!(state.nbt().and_then(|nbt| nbt.get("Lock")), Some(&NbtTag::String("key".into())));
+
+        let tag = read_block_state(&mut StringReader::new("#minecraft:logs[axis=y]"), Some(&Blocks), true)?;
+        assert!(tag.target().is_tag());
+        assert!(read_block_state(&mut StringReader::new("#minecraft:logs"), Some(&Blocks), false).is_err());
+        assert!(read_block_state(&mut StringReader::new("dirt"), Some(&Blocks), false).is_err());
+        assert!(read_block_state(&mut StringReader::new("oak_stairs[facing=up]"), Some(&Blocks), false).is_err());
+        assert!(read_block_state(&mut StringReader::new("oak_stairs[half=top,half=top]"), Some(&Blocks), false).is_err());
+        assert!(read_block_state(&mut StringReader::new("oak_stairs[shape=straight]"), Some(&Blocks), false).is_err());
+        assert!(read_block_state(&mut StringReader::new("oak_stairs[shape=straight]"), None, false).is_ok());
+
+        let item = read_item_stack(&mut StringReader::new("diamond_sword{Damage:3}"), None, false)?;
+        assert_eq!(item.target().location(), "minecraft:diamond_sword");
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_block_suggestions() {
+        assert_eq!(suggest_block("oak", &Blocks, true), vec!["minecraft:oak_stairs"]);
+        assert_eq!(suggest_block("#", &Blocks, true), vec!["#minecraft:logs"]);
+        assert_eq!(suggest_block("oak_stairs[facing=north,", &Blocks, false), vec!["oak_stairs[facing=north,half="]);
+        assert_eq!(suggest_block("oak_stairs[half=t", &Blocks, false), vec!["oak_stairs[half=top"]);
+    }
+}
@@ -0,0 +1,342 @@
+use crate::arguments::entity::{read_selector, EntityResolver, EntitySelector};
+use crate::arguments::reader::StringReader;
+use crate::arguments::reader_parser;
+use crate::executor::command::ParserFunction;
+use crate::executor::context::Value;
+use serde_json::{Map, Value as Json};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClickEvent {
+    pub action: String,
+    pub value: String,
+}
+
+/// Styling shared by every kind of text component; unset fields inherit from the parent.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Style {
+    pub color: Option<String>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub font: Option<String>,
+    pub insertion: Option<String>,
+    pub click_event: Option<ClickEvent>,
+    /// Hover events carry nested components or item and entity data, so they're kept as JSON.
+    pub hover_event: Option<Json>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtSource {
+    Block(String),
+    Entity(String),
+    Storage(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentContent {
+    Text(String),
+    Translatable { key: String, with: Vec<TextComponent> },
+    Score { name: String, objective: String },
+    Selector { selector: String, separator: Option<Box<TextComponent>> },
+    Keybind(String),
+    Nbt { path: String, interpret: bool, separator: Option<Box<TextComponent>>, source: NbtSource },
+}
+
+/// Typed JSON text component, as accepted by `minecraft:component` arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextComponent {
+    content: ComponentContent,
+    style: Style,
+    extra: Vec<TextComponent>,
+}
+
+fn string_field(object: &Map<String, Json>, key: &str) -> anyhow::Result<Option<String>> {
+    match object.get(key) {
+        None => Ok(None),
+        Some(Json::String(value)) => Ok(Some(value.clone())),
+        Some(other) => anyhow::bail!("Expected {} to be a string, was {}", key, other),
+    }
+}
+
+fn bool_field(object: &Map<String, Json>, key: &str) -> anyhow::Result<Option<bool>> {
+    match object.get(key) {
+        None => Ok(None),
+        Some(Json::Bool(value)) => Ok(Some(*value)),
+        Some(other) => anyhow::bail!("Expected {} to be a boolean, was {}", key, other),
+    }
+}
+
+fn separator_field(object: &Map<String, Json>) -> anyhow::Result<Option<Box<TextComponent>>> {
+    object.get("separator").map(|separator| TextComponent::from_json(separator).map(Box::new)).transpose()
+}
+
+impl Style {
+    fn from_json(object: &Map<String, Json>) -> anyhow::Result<Self> {
+        let click_event = match object.get("clickEvent") {
+            None => None,
+            Some(Json::Object(event)) => Some(ClickEvent {
+                action: string_field(event, "action")?.ok_or_else(|| anyhow::anyhow!("Click event is missing an action"))?,
+                value: string_field(event, "value")?.ok_or_else(|| anyhow::anyhow!("Click event is missing a value"))?,
+            }),
+            Some(other) => anyhow::bail!("Expected clickEvent to be an object, was {}", other),
+        };
+        Ok(Self {
+            color: string_field(object, "color")?,
+            bold: bool_field(object, "bold")?,
+            italic: bool_field(object, "italic")?,
+            underlined: bool_field(object, "underlined")?,
+            strikethrough: bool_field(object, "strikethrough")?,
+            obfuscated: bool_field(object, "obfuscated")?,
+            font: string_field(object, "font")?,
+            insertion: string_field(object, "insertion")?,
+            click_event,
+            hover_event: object.get("hoverEvent").cloned(),
+        })
+    }
+}
+
+impl ComponentContent {
+    fn from_json(object: &Map<String, Json>) -> anyhow::Result<Self> {
+        if let Some(text) = string_field(object, "text")? {
+            return Ok(ComponentContent::Text(text));
+        }
+        if let Some(key) = string_field(object, "translate")? {
+            let with = match object.get("with") {
+                None => Vec::new(),
+                Some(Json::Array(arguments)) => arguments.iter().map(TextComponent::from_json).collect::<anyhow::Result<_>>()?,
+                Some(other) => anyhow::bail!("Expected with to be an array, was {}", other),
+            };
+            return Ok(ComponentContent::Translatable { key, with });
+        }
+        if let Some(score) = object.get("score") {
+            let score = match score {
+                Json::Object(score) => score,
+                other => anyhow::bail!("Expected score to be an object, was {}", other),
+            };
+            return match (string_field(score, "name")?, string_field(score, "objective")?) {
+                (Some(name), Some(objective)) => Ok(ComponentContent::Score { name, objective }),
+                _ => anyhow::bail!("A score component needs at least a name and an objective"),
+            };
+        }
+        if let Some(selector) = string_field(object, "selector")? {
+            return Ok(ComponentContent::Selector { selector, separator: separator_field(object)? });
+        }
+        if let Some(keybind) = string_field(object, "keybind")? {
+            return Ok(ComponentContent::Keybind(keybind));
+        }
+        if let Some(path) = string_field(object, "nbt")? {
+            let source = if let Some(block) = string_field(object, "block")? {
+                NbtSource::Block(block)
+            } else if let Some(entity) = string_field(object, "entity")? {
+                NbtSource::Entity(entity)
+            } else if let Some(storage) = string_field(object, "storage")? {
+                NbtSource::Storage(storage)
+            } else {
+                anyhow::bail!("An nbt component needs a block, entity or storage source");
+            };
+            let interpret = bool_field(object, "interpret")?.unwrap_or(false);
+            return Ok(ComponentContent::Nbt { path, interpret, separator: separator_field(object)?, source });
+        }
+        anyhow::bail!("Don't know how to turn {} into a Component", Json::Object(object.clone()))
+    }
+}
+
+impl TextComponent {
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self { content: ComponentContent::Text(text.into()), style: Style::default(), extra: Vec::new() }
+    }
+
+    /// Converts JSON the way vanilla does: primitives become text and arrays append their tail
+    /// to the first element.
+    pub fn from_json(json: &Json) -> anyhow::Result<Self> {
+        match json {
+            Json::String(text) => Ok(Self::text(text.clone())),
+            Json::Number(number) => Ok(Self::text(number.to_string())),
+            Json::Bool(value) => Ok(Self::text(value.to_string())),
+            Json::Array(elements) => {
+                let (first, rest) = elements.split_first().ok_or_else(|| anyhow::anyhow!("Unexpected empty array of components"))?;
+                let mut component = Self::from_json(first)?;
+                for element in rest {
+                    component.extra.push(Self::from_json(element)?);
+                }
+                Ok(component)
+            }
+            Json::Object(object) => {
+                let extra = match object.get("extra") {
+                    None => Vec::new(),
+                    Some(Json::Array(extra)) if extra.is_empty() => anyhow::bail!("Unexpected empty array of components"),
+                    Some(Json::Array(extra)) => extra.iter().map(Self::from_json).collect::<anyhow::Result<_>>()?,
+                    Some(other) => anyhow::bail!("Expected extra to be an array, was {}", other),
+                };
+                Ok(Self { content: ComponentContent::from_json(object)?, style: Style::from_json(object)?, extra })
+            }
+            Json::Null => anyhow::bail!("Don't know how to turn null into a Component"),
+        }
+    }
+
+    pub fn content(&self) -> &ComponentContent {
+        &self.content
+    }
+
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+
+    pub fn extra(&self) -> &Vec<TextComponent> {
+        &self.extra
+    }
+
+    /// Text of this component and its siblings, without styling or resolving non text content.
+    pub fn plain_text(&self) -> String {
+        let mut text = match &self.content {
+            ComponentContent::Text(text) => text.clone(),
+            _ => String::new(),
+        };
+        for sibling in &self.extra {
+            text.push_str(&sibling.plain_text());
+        }
+        text
+    }
+}
+
+/// Reads a single JSON value at the cursor and converts it into a component.
+pub fn read_component(reader: &mut StringReader) -> anyhow::Result<TextComponent> {
+    let mut values = serde_json::Deserializer::from_str(reader.remaining()).into_iter::<Json>();
+    let json = match values.next() {
+        Some(Ok(json)) => json,
+        Some(Err(error)) => return Err(reader.error(format!("Invalid chat component: {}", error))),
+        None => return Err(reader.error("Invalid chat component: expected a value")),
+    };
+    let component = TextComponent::from_json(&json).map_err(|error| reader.error(format!("Invalid chat component: {}", error)))?;
+    reader.set_cursor(reader.cursor() + values.byte_offset());
+    Ok(component)
+}
+
+/// Selector found within a message, spanning `start..end` of the message text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageSelector {
+    start: usize,
+    end: usize,
+    selector: EntitySelector,
+}
+
+impl MessageSelector {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn selector(&self) -> &EntitySelector {
+        &self.selector
+    }
+}
+
+/// Parsed `minecraft:message` argument, the remaining input with the selectors it mentions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    text: String,
+    selectors: Vec<MessageSelector>,
+}
+
+impl Message {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn selectors(&self) -> &Vec<MessageSelector> {
+        &self.selectors
+    }
+
+    /// Replaces every selector with the comma separated names of the entities it selects.
+    pub fn resolve<T, R, F>(&self, resolver: &R, sender: &T, name: F) -> anyhow::Result<String>
+    where
+        R: EntityResolver<T>,
+        F: Fn(&R::Entity) -> String,
+    {
+        let mut resolved = String::new();
+        let mut last = 0;
+        for part in &self.selectors {
+            resolved.push_str(&self.text[last..part.start]);
+            let names = part.selector.resolve(resolver, sender)?.iter().map(&name).collect::<Vec<String>>();
+            resolved.push_str(&names.join(", "));
+            last = part.end;
+        }
+        resolved.push_str(&self.text[last..]);
+        Ok(resolved)
+    }
+}
+
+/// Reads the rest of the input as a message, recording every `@p`, `@a`, `@r`, `@s` or `@e`
+/// selector it contains.
+pub fn read_message(reader: &mut StringReader) -> anyhow::Result<Message> {
+    let start = reader.cursor();
+    let mut selectors = Vec::new();
+    while reader.can_read() {
+        if reader.peek() == Some('@') && matches!(reader.peek_at(1), Some('p' | 'a' | 'r' | 's' | 'e')) {
+            let selector_start = reader.cursor();
+            let selector = read_selector(reader)?;
+            selectors.push(MessageSelector { start: selector_start - start, end: reader.cursor() - start, selector });
+        } else {
+            reader.skip();
+        }
+    }
+    Ok(Message { text: reader.input()[start..].to_string(), selectors })
+}
+
+pub fn component_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Component(Box::new(read_component(reader)?))))
+}
+
+pub fn message_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Message(read_message(reader)?)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::chat::{read_component, read_message, ComponentContent};
+    use crate::arguments::entity::{EntityResolver, EntitySelector, Target, TargetVariable};
+    use crate::arguments::reader::StringReader;
+
+    struct Players;
+
+    impl EntityResolver<()> for Players {
+        type Entity = String;
+
+        fn select(&self, _: &(), selector: &EntitySelector) -> anyhow::Result<Vec<String>> {
+            match selector.target() {
+                Target::Variable(TargetVariable::AllPlayers) => Ok(vec!["Alex".into(), "Steve".into()]),
+                _ => Ok(vec!["Steve".into()]),
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_component() -> anyhow::Result<()> {
+        let mut reader = StringReader::new("[\"Hello \", {\"text\": \"world\", \"bold\": true}] trailing");
+        let component = read_component(&mut reader)?;
+        assert_eq!(component.plain_text(), "Hello world");
+        assert_eq!(component.extra()[0].style().bold, Some(true));
+        assert_eq!(reader.remaining(), " trailing");
+
+        let translated = read_component(&mut StringReader::new("{\"translate\": \"chat.type.text\", \"with\": [\"a\", 1]}"))?;
+        assert!(matches!(translated.content(), ComponentContent::Translatable { with, .. } if with.len() == 2));
+        assert!(read_component(&mut StringReader::new("{\"color\": \"red\"}")).is_err());
+        assert!(read_component(&mut StringReader::new("{\"text\": ")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_message() -> anyhow::Result<()> {
+        let message = read_message(&mut StringReader::new("Hello @p and @a[limit=2], bye @"))?;
+        assert_eq!(message.selectors().len(), 2);
+        assert_eq!((message.selectors()[0].start(), message.selectors()[0].end()), (6, 8));
+        assert_eq!(message.resolve(&Players, &(), |name| name.clone())?, "Hello Steve and Alex, Steve, bye @");
+        assert!(read_message(&mut StringReader::new("Hi @e[limit=0]")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,400 @@
+use crate::arguments::reader::StringReader;
+use crate::arguments::reader_parser;
+use crate::executor::command::ParserFunction;
+use crate::executor::context::Value;
+
+/// Implemented by senders which coordinates can be resolved against.
+pub trait PositionSource {
+    /// Position of the sender as x, y and z.
+    fn position(&self) -> (f64, f64, f64);
+
+    /// Rotation of the sender as yaw (y rotation) and pitch (x rotation), in degrees.
+    fn rotation(&self) -> (f32, f32);
+}
+
+/// Single world coordinate, either absolute or `~` relative to the sender.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldCoordinate {
+    relative: bool,
+    value: f64,
+}
+
+impl WorldCoordinate {
+    pub fn new(relative: bool, value: f64) -> Self {
+        Self { relative, value }
+    }
+
+    pub fn is_relative(&self) -> bool {
+        self.relative
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn resolve(&self, origin: f64) -> f64 {
+        if self.relative {
+            origin + self.value
+        } else {
+            self.value
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinates {
+    World { x: WorldCoordinate, y: WorldCoordinate, z: WorldCoordinate },
+    Local { left: f64, up: f64, forwards: f64 },
+}
+
+impl Coordinates {
+    pub fn is_x_relative(&self) -> bool {
+        match self {
+            Coordinates::World { x, .. } => x.relative,
+            Coordinates::Local { .. } => true,
+        }
+    }
+
+    pub fn is_y_relative(&self) -> bool {
+        match self {
+            Coordinates::World { y, .. } => y.relative,
+            Coordinates::Local { .. } => true,
+        }
+    }
+
+    pub fn is_z_relative(&self) -> bool {
+        match self {
+            Coordinates::World { z, .. } => z.relative,
+            Coordinates::Local { .. } => true,
+        }
+    }
+
+    /// Resolves the coordinates against the position and rotation of the source.
+    pub fn position<S: PositionSource>(&self, source: &S) -> (f64, f64, f64) {
+        let (origin_x, origin_y, origin_z) = source.position();
+        match self {
+            Coordinates::World { x, y, z } => (x.resolve(origin_x), y.resolve(origin_y), z.resolve(origin_z)),
+            Coordinates::Local { left, up, forwards } => {
+                let (yaw, pitch) = source.rotation();
+                let yaw = ((yaw + 90.0) as f64).to_radians();
+                let (pitch_forwards, pitch_up) = ((-pitch) as f64, (-pitch + 90.0) as f64);
+                let forwards_axis = (
+                    yaw.cos() * pitch_forwards.to_radians().cos(),
+                    pitch_forwards.to_radians().sin(),
+                    yaw.sin() * pitch_forwards.to_radians().cos(),
+                );
+                let up_axis = (
+                    yaw.cos() * pitch_up.to_radians().cos(),
+                    pitch_up.to_radians().sin(),
+                    yaw.sin() * pitch_up.to_radians().cos(),
+                );
+                let left_axis = (
+                    -(forwards_axis.1 * up_axis.2 - forwards_axis.2 * up_axis.1),
+                    -(forwards_axis.2 * up_axis.0 - forwards_axis.0 * up_axis.2),
+                    -(forwards_axis.0 * up_axis.1 - forwards_axis.1 * up_axis.0),
+                );
+                (
+                    origin_x + forwards_axis.0 * forwards + up_axis.0 * up + left_axis.0 * left,
+                    origin_y + forwards_axis.1 * forwards + up_axis.1 * up + left_axis.1 * left,
+                    origin_z + forwards_axis.2 * forwards + up_axis.2 * up + left_axis.2 * left,
+                )
+            }
+        }
+    }
+
+    /// Resolves the coordinates to the block containing the resolved position.
+    pub fn block_position<S: PositionSource>(&self, source: &S) -> (i32, i32, i32) {
+        let (x, y, z) = self.position(source);
+        (x.floor() as i32, y.floor() as i32, z.floor() as i32)
+    }
+}
+
+/// Parsed `minecraft:rotation` argument, given as yaw then pitch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    yaw: WorldCoordinate,
+    pitch: WorldCoordinate,
+}
+
+impl Rotation {
+    pub fn new(yaw: WorldCoordinate, pitch: WorldCoordinate) -> Self {
+        Self { yaw, pitch }
+    }
+
+    pub fn yaw(&self) -> WorldCoordinate {
+        self.yaw
+    }
+
+    pub fn pitch(&self) -> WorldCoordinate {
+        self.pitch
+    }
+
+    /// Resolves the rotation against the source as yaw and pitch, in degrees.
+    pub fn resolve<S: PositionSource>(&self, source: &S) -> (f32, f32) {
+        let (yaw, pitch) = source.rotation();
+        (self.yaw.resolve(yaw as f64) as f32, self.pitch.resolve(pitch as f64) as f32)
+    }
+}
+
+/// Parsed `minecraft:angle` argument, a yaw which may be relative to the sender.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Angle(WorldCoordinate);
+
+impl Angle {
+    pub fn coordinate(&self) -> WorldCoordinate {
+        self.0
+    }
+
+    /// Resolves the angle against the yaw of the source, wrapped to `-180..180` degrees.
+    pub fn resolve<S: PositionSource>(&self, source: &S) -> f32 {
+        let (yaw, _) = source.rotation();
+        let angle = self.0.resolve(yaw as f64) as f32;
+        (angle + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// Parsed `minecraft:swizzle` argument, a set of axes such as `xz`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Swizzle {
+    x: bool,
+    y: bool,
+    z: bool,
+}
+
+impl Swizzle {
+    pub fn contains(&self, axis: Axis) -> bool {
+        match axis {
+            Axis::X => self.x,
+            Axis::Y => self.y,
+            Axis::Z => self.z,
+        }
+    }
+}
+
+const MIXED_TYPE: &str = "Cannot mix world & local coordinates (everything must either use ^ or not)";
+
+fn is_separator(reader: &StringReader) -> bool {
+    !reader.can_read() || reader.peek() == Some(' ')
+}
+
+fn read_world(reader: &mut StringReader, integer: bool, center_correct: bool) -> anyhow::Result<WorldCoordinate> {
+    match reader.peek() {
+        Some('^') => return Err(reader.error(MIXED_TYPE)),
+        None => return Err(reader.error(if integer { "Expected integer" } else { "Expected double" })),
+        _ => {}
+    }
+    let relative = reader.peek() == Some('~');
+    if relative {
+        reader.skip();
+        if is_separator(reader) {
+            return Ok(WorldCoordinate::new(true, 0.0));
+        }
+    }
+    let start = reader.cursor();
+    let value = if integer && !relative { reader.read_int()? as f64 } else { reader.read_double()? };
+    let corrected = center_correct && !relative && !reader.input()[start..reader.cursor()].contains('.');
+    Ok(WorldCoordinate::new(relative, if corrected { value + 0.5 } else { value }))
+}
+
+fn read_local(reader: &mut StringReader) -> anyhow::Result<f64> {
+    if reader.peek() != Some('^') {
+        return Err(reader.error(MIXED_TYPE));
+    }
+    reader.skip();
+    if is_separator(reader) {
+        Ok(0.0)
+    } else {
+        reader.read_double()
+    }
+}
+
+fn expect_separator(reader: &mut StringReader, count: usize) -> anyhow::Result<()> {
+    if reader.peek() != Some(' ') {
+        return Err(reader.error(format!("Incomplete (expected {} coordinates)", count)));
+    }
+    reader.skip();
+    Ok(())
+}
+
+/// Reads three coordinates, either all local with `^` or world coordinates with optional `~`.
+pub fn read_coordinates(reader: &mut StringReader, integer: bool, center_correct: bool) -> anyhow::Result<Coordinates> {
+    if reader.peek() == Some('^') {
+        let left = read_local(reader)?;
+        expect_separator(reader, 3)?;
+        let up = read_local(reader)?;
+        expect_separator(reader, 3)?;
+        let forwards = read_local(reader)?;
+        return Ok(Coordinates::Local { left, up, forwards });
+    }
+    if !reader.can_read() {
+        return Err(reader.error("Incomplete (expected 3 coordinates)"));
+    }
+    let x = read_world(reader, integer, center_correct)?;
+    expect_separator(reader, 3)?;
+    let y = read_world(reader, integer, false)?;
+    expect_separator(reader, 3)?;
+    let z = read_world(reader, integer, center_correct)?;
+    Ok(Coordinates::World { x, y, z })
+}
+
+/// Reads two horizontal world coordinates; the y coordinate stays relative to the sender.
+pub fn read_horizontal(reader: &mut StringReader, integer: bool, center_correct: bool) -> anyhow::Result<Coordinates> {
+    if !reader.can_read() {
+        return Err(reader.error("Incomplete (expected 2 coordinates)"));
+    }
+    let x = read_world(reader, integer, center_correct)?;
+    expect_separator(reader, 2)?;
+    let z = read_world(reader, integer, center_correct)?;
+    Ok(Coordinates::World { x, y: WorldCoordinate::new(true, 0.0), z })
+}
+
+pub fn read_rotation(reader: &mut StringReader) -> anyhow::Result<Rotation> {
+    if !reader.can_read() {
+        return Err(reader.error("Incomplete (expected 2 coordinates)"));
+    }
+    let yaw = read_world(reader, false, false)?;
+    expect_separator(reader, 2)?;
+    let pitch = read_world(reader, false, false)?;
+    Ok(Rotation::new(yaw, pitch))
+}
+
+pub fn read_angle(reader: &mut StringReader) -> anyhow::Result<Angle> {
+    if !reader.can_read() {
+        return Err(reader.error("Incomplete (expected 1 angle)"));
+    }
+    let start = reader.cursor();
+    let angle = read_world(reader, false, false)?;
+    if !angle.value().is_finite() {
+        reader.set_cursor(start);
+        return Err(reader.error("Invalid angle"));
+    }
+    Ok(Angle(angle))
+}
+
+pub fn read_swizzle(reader: &mut StringReader) -> anyhow::Result<Swizzle> {
+    let start = reader.cursor();
+    let mut swizzle = Swizzle::default();
+    while reader.can_read() && reader.peek() != Some(' ') {
+        let axis = match reader.read() {
+            Some('x') => &mut swizzle.x,
+            Some('y') => &mut swizzle.y,
+            Some('z') => &mut swizzle.z,
+            _ => {
+                reader.set_cursor(start);
+                return Err(reader.error("Invalid swizzle, expected combination of 'x', 'y' and 'z'"));
+            }
+        };
+        if *axis {
+            reader.set_cursor(start);
+            return Err(reader.error("Invalid swizzle, expected combination of 'x', 'y' and 'z'"));
+        }
+        *axis = true;
+    }
+    if swizzle == Swizzle::default() {
+        return Err(reader.error("Invalid swizzle, expected combination of 'x', 'y' and 'z'"));
+    }
+    Ok(swizzle)
+}
+
+pub fn block_pos_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Coordinates(read_coordinates(reader, true, false)?)))
+}
+
+pub fn vec3_parser<T>(center_correct: bool) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::Coordinates(read_coordinates(reader, false, center_correct)?)))
+}
+
+pub fn column_pos_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Coordinates(read_horizontal(reader, true, false)?)))
+}
+
+pub fn vec2_parser<T>(center_correct: bool) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::Coordinates(read_horizontal(reader, false, center_correct)?)))
+}
+
+pub fn rotation_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Rotation(read_rotation(reader)?)))
+}
+
+pub fn angle_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Angle(read_angle(reader)?)))
+}
+
+pub fn swizzle_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Swizzle(read_swizzle(reader)?)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::coordinates::{read_angle, read_coordinates, read_horizontal, read_rotation, read_swizzle, Axis, Coordinates, PositionSource};
+    use crate::arguments::reader::StringReader;
+
+    struct Source;
+
+    impl PositionSource for Source {
+        fn position(&self) -> (f64, f64, f64) {
+            (10.0, 64.0, -5.0)
+        }
+
+        fn rotation(&self) -> (f32, f32) {
+            (0.0, 0.0)
+        }
+    }
+
+    fn approximately(actual: (f64, f64, f64), expected: (f64, f64, f64)) -> bool {
+        (actual.0 - expected.0).abs() < 1e-6 && (actual.1 - expected.1).abs() < 1e-6 && (actual.2 - expected.2).abs() < 1e-6
+    }
+
+    #[test]
+    pub fn test_world_coordinates() -> anyhow::Result<()> {
+        let coordinates = read_coordinates(&mut StringReader::new("~ ~1 -3"), false, true)?;
+        assert!(coordinates.is_x_relative() && !coordinates.is_z_relative());
+        assert!(approximately(coordinates.position(&Source), (10.0, 65.0, -2.5)));
+
+        let block = read_coordinates(&mut StringReader::new("~-1 70 ~0.5"), true, false)?;
+        assert_eq!(block.block_position(&Source), (9, 70, -5));
+        assert!(read_coordinates(&mut StringReader::new("1.5 2 3"), true, false).is_err());
+        assert!(read_coordinates(&mut StringReader::new("1 2"), false, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_local_coordinates() -> anyhow::Result<()> {
+        let forwards = read_coordinates(&mut StringReader::new("^ ^ ^2"), false, true)?;
+        assert_eq!(forwards, Coordinates::Local { left: 0.0, up: 0.0, forwards: 2.0 });
+        assert!(approximately(forwards.position(&Source), (10.0, 64.0, -3.0)));
+        let left = read_coordinates(&mut StringReader::new("^1 ^ ^"), false, true)?;
+        assert!(approximately(left.position(&Source), (11.0, 64.0, -5.0)));
+
+        assert!(read_coordinates(&mut StringReader::new("~ ^ ~"), false, true).is_err());
+        assert!(read_coordinates(&mut StringReader::new("^ ~ ^"), false, true).is_err());
+        assert!(read_horizontal(&mut StringReader::new("^ ^"), false, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_rotation() -> anyhow::Result<()> {
+        let rotation = read_rotation(&mut StringReader::new("~90 45"))?;
+        assert_eq!(rotation.resolve(&Source), (90.0, 45.0));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_angle_and_swizzle() -> anyhow::Result<()> {
+        assert_eq!(read_angle(&mut StringReader::new("~270"))?.resolve(&Source), -90.0);
+        assert!(read_angle(&mut StringReader::new("^1")).is_err());
+
+        let swizzle = read_swizzle(&mut StringReader::new("xz"))?;
+        assert!(swizzle.contains(Axis::X) && !swizzle.contains(Axis::Y) && swizzle.contains(Axis::Z));
+        assert!(read_swizzle(&mut StringReader::new("xx")).is_err());
+        assert!(read_swizzle(&mut StringReader::new("w")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,601 @@
+use crate::arguments::range::MinMaxBounds;
+use crate::arguments::nbt::{read_compound, NbtCompound};
+use crate::arguments::profile::parse_uuid;
+use crate::arguments::reader::StringReader;
+use crate::arguments::{fixed_suggestions, reader_parser};
+use crate::executor::command::{ParserFunction, SuggestionFunction};
+use crate::executor::context::Value;
+use crate::protocol::{EntitySelector as SelectorBits, ScoreHolderSelector};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetVariable {
+    NearestPlayer,
+    RandomPlayer,
+    AllPlayers,
+    AllEntities,
+    Executor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Variable(TargetVariable),
+    Player(String),
+    Uuid(u128),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Negatable<V> {
+    pub value: V,
+    pub negated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdvancementPredicate {
+    Done(bool),
+    Criteria(Vec<(String, bool)>),
+}
+
+/// Options given between the brackets of a target variable, e.g. `@e[type=!player,limit=3]`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SelectorOptions {
+    pub name: Vec<Negatable<String>>,
+    pub distance: Option<MinMaxBounds<f64>>,
+    pub level: Option<MinMaxBounds<i32>>,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    pub dx: Option<f64>,
+    pub dy: Option<f64>,
+    pub dz: Option<f64>,
+    pub x_rotation: Option<MinMaxBounds<f64>>,
+    pub y_rotation: Option<MinMaxBounds<f64>>,
+    pub limit: Option<i32>,
+    pub sort: Option<Sort>,
+    pub gamemode: Vec<Negatable<String>>,
+    pub team: Vec<Negatable<String>>,
+    pub entity_type: Vec<Negatable<String>>,
+    pub type_tag: Vec<Negatable<String>>,
+    pub tag: Vec<Negatable<String>>,
+    pub nbt: Vec<Negatable<NbtCompound>>,
+    pub scores: Vec<(String, MinMaxBounds<i32>)>,
+    pub advancements: Vec<(String, AdvancementPredicate)>,
+    pub predicate: Vec<Negatable<String>>,
+}
+
+/// Parsed `minecraft:entity` argument: a target variable with options, a player name or a UUID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntitySelector {
+    target: Target,
+    options: SelectorOptions,
+}
+
+/// Implemented by the host server to find the entities a selector matches.
+pub trait EntityResolver<T> {
+    type Entity;
+
+    /// Returns the entities matching the selector from the point of view of the sender, in the
+    /// order requested by the selector.
+    fn select(&self, sender: &T, selector: &EntitySelector) -> anyhow::Result<Vec<Self::Entity>>;
+}
+
+impl EntitySelector {
+    pub fn new(target: Target, options: SelectorOptions) -> Self {
+        Self { target, options }
+    }
+
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    pub fn options(&self) -> &SelectorOptions {
+        &self.options
+    }
+
+    pub fn is_current_entity(&self) -> bool {
+        self.target == Target::Variable(TargetVariable::Executor)
+    }
+
+    /// Upper bound on the number of entities this selector can match.
+    pub fn max_results(&self) -> usize {
+        if let Some(limit) = self.options.limit {
+            return usize::try_from(limit).unwrap_or(0);
+        }
+        match self.target {
+            Target::Variable(TargetVariable::AllPlayers | TargetVariable::AllEntities) => usize::MAX,
+            _ => 1,
+        }
+    }
+
+    /// Whether this selector may match entities which are not players.
+    pub fn includes_entities(&self) -> bool {
+        let players_by_type = self
+            .options
+            .entity_type
+            .iter()
+            .any(|entity_type| !entity_type.negated && is_player_type(&entity_type.value));
+        match self.target {
+            Target::Variable(TargetVariable::AllEntities | TargetVariable::Executor) => {
+                !players_by_type && self.options.level.is_none() && self.options.gamemode.is_empty()
+            }
+            Target::Variable(_) | Target::Player(_) => false,
+            Target::Uuid(_) => true,
+        }
+    }
+
+    pub fn sort(&self) -> Sort {
+        match (self.options.sort, &self.target) {
+            (Some(sort), _) => sort,
+            (None, Target::Variable(TargetVariable::NearestPlayer)) => Sort::Nearest,
+            (None, Target::Variable(TargetVariable::RandomPlayer)) => Sort::Random,
+            (None, _) => Sort::Arbitrary,
+        }
+    }
+
+    /// Validates the selector against the `minecraft:entity` properties of a node.
+    pub fn validate(&self, bits: &SelectorBits) -> anyhow::Result<()> {
+        if bits.is_single() && self.max_results() > 1 {
+            anyhow::bail!("Only one entity is allowed, but the provided selector allows more than one");
+        }
+        if bits.is_players_only() && self.includes_entities() && !self.is_current_entity() {
+            anyhow::bail!("Only players may be affected by this command, but the provided selector includes entities");
+        }
+        Ok(())
+    }
+
+    pub fn resolve<T, R: EntityResolver<T>>(&self, resolver: &R, sender: &T) -> anyhow::Result<Vec<R::Entity>> {
+        let mut entities = resolver.select(sender, self)?;
+        entities.truncate(self.max_results());
+        Ok(entities)
+    }
+
+    pub fn resolve_single<T, R: EntityResolver<T>>(&self, resolver: &R, sender: &T) -> anyhow::Result<R::Entity> {
+        let mut entities = self.resolve(resolver, sender)?;
+        match entities.len() {
+            0 => anyhow::bail!("No entity was found"),
+            1 => Ok(entities.remove(0)),
+            _ => anyhow::bail!("Only one entity is allowed, but the provided selector allows more than one"),
+        }
+    }
+}
+
+fn is_player_type(entity_type: &str) -> bool {
+    entity_type == "player" || entity_type == "minecraft:player"
+}
+
+fn read_negatable(reader: &mut StringReader) -> bool {
+    reader.skip_whitespace();
+    if reader.peek() == Some('!') {
+        reader.skip();
+        reader.skip_whitespace();
+        true
+    } else {
+        false
+    }
+}
+
+fn read_positive_once(
+    reader: &mut StringReader,
+    option: &str,
+    values: &mut Vec<Negatable<String>>,
+    read: fn(&mut StringReader) -> anyhow::Result<String>,
+) -> anyhow::Result<()> {
+    let start = reader.cursor();
+    let negated = read_negatable(reader);
+    if !negated && values.iter().any(|value| !value.negated) {
+        reader.set_cursor(start);
+        return Err(reader.error(format!("Option '{}' isn't applicable here", option)));
+    }
+    let value = read(reader)?;
+    values.push(Negatable { value, negated });
+    Ok(())
+}
+
+fn read_once<V>(reader: &mut StringReader, option: &str, slot: &mut Option<V>, start: usize, value: V) -> anyhow::Result<()> {
+    if slot.is_some() {
+        reader.set_cursor(start);
+        return Err(reader.error(format!("Option '{}' isn't applicable here", option)));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn read_map<V, F>(reader: &mut StringReader, mut read_entry: F) -> anyhow::Result<Vec<(String, V)>>
+where
+    F: FnMut(&mut StringReader) -> anyhow::Result<V>,
+{
+    let mut entries = Vec::new();
+    reader.expect('{')?;
+    reader.skip_whitespace();
+    while reader.can_read() && reader.peek() != Some('}') {
+        reader.skip_whitespace();
+        let key = reader.read_while(|c| c != '=' && c != '}' && c != ',' && !c.is_whitespace()).to_string();
+        reader.skip_whitespace();
+        reader.expect('=')?;
+        reader.skip_whitespace();
+        entries.push((key, read_entry(reader)?));
+        reader.skip_whitespace();
+        if reader.peek() == Some(',') {
+            reader.skip();
+        }
+    }
+    reader.expect('}')?;
+    Ok(entries)
+}
+
+fn read_option(reader: &mut StringReader, target: &Target, options: &mut SelectorOptions) -> anyhow::Result<()> {
+    let start = reader.cursor();
+    let option = reader.read_string()?;
+    reader.skip_whitespace();
+    reader.expect('=')?;
+    reader.skip_whitespace();
+    let value_start = reader.cursor();
+    let is_executor = *target == Target::Variable(TargetVariable::Executor);
+    match option.as_str() {
+        "name" => read_positive_once(reader, "name", &mut options.name, |reader| reader.read_string())?,
+        "distance" => {
+            let distance = MinMaxBounds::<f64>::read(reader)?;
+            if distance.min().is_some_and(|min| min < 0.0) {
+                reader.set_cursor(value_start);
+                return Err(reader.error("Distance cannot be negative"));
+            }
+            read_once(reader, "distance", &mut options.distance, start, distance)?;
+        }
+        "level" => {
+            let level = MinMaxBounds::<i32>::read(reader)?;
+            if level.min().is_some_and(|min| min < 0) {
+                reader.set_cursor(value_start);
+                return Err(reader.error("Level cannot be negative"));
+            }
+            read_once(reader, "level", &mut options.level, start, level)?;
+        }
+        "x" => {
+            let value = reader.read_double()?;
+            read_once(reader, "x", &mut options.x, start, value)?;
+        }
+        "y" => {
+            let value = reader.read_double()?;
+            read_once(reader, "y", &mut options.y, start, value)?;
+        }
+        "z" => {
+            let value = reader.read_double()?;
+            read_once(reader, "z", &mut options.z, start, value)?;
+        }
+        "dx" => {
+            let value = reader.read_double()?;
+            read_once(reader, "dx", &mut options.dx, start, value)?;
+        }
+        "dy" => {
+            let value = reader.read_double()?;
+            read_once(reader, "dy", &mut options.dy, start, value)?;
+        }
+        "dz" => {
+            let value = reader.read_double()?;
+            read_once(reader, "dz", &mut options.dz, start, value)?;
+        }
+        "x_rotation" => {
+            let rotation = MinMaxBounds::<f64>::read_wrapped(reader)?;
+            read_once(reader, "x_rotation", &mut options.x_rotation, start, rotation)?;
+        }
+        "y_rotation" => {
+            let rotation = MinMaxBounds::<f64>::read_wrapped(reader)?;
+            read_once(reader, "y_rotation", &mut options.y_rotation, start, rotation)?;
+        }
+        "limit" if !is_executor => {
+            let limit = reader.read_int()?;
+            if limit < 1 {
+                reader.set_cursor(value_start);
+                return Err(reader.error("Limit must be at least 1"));
+            }
+            read_once(reader, "limit", &mut options.limit, start, limit)?;
+        }
+        "sort" if !is_executor => {
+            let sort = match reader.read_unquoted_string() {
+                "nearest" => Sort::Nearest,
+                "furthest" => Sort::Furthest,
+                "random" => Sort::Random,
+                "arbitrary" => Sort::Arbitrary,
+                other => {
+                    reader.set_cursor(value_start);
+                    return Err(reader.error(format!("Invalid or unknown sort type '{}'", other)));
+                }
+            };
+            read_once(reader, "sort", &mut options.sort, start, sort)?;
+        }
+        "gamemode" => read_positive_once(reader, "gamemode", &mut options.gamemode, |reader| {
+            let start = reader.cursor();
+            match reader.read_unquoted_string() {
+                gamemode @ ("survival" | "creative" | "adventure" | "spectator") => Ok(gamemode.to_string()),
+                other => {
+                    reader.set_cursor(start);
+                    Err(reader.error(format!("Invalid or unknown game mode '{}'", other)))
+                }
+            }
+        })?,
+        "team" => read_positive_once(reader, "team", &mut options.team, |reader| Ok(reader.read_unquoted_string().to_string()))?,
+        "type" => {
+            let applicable = match target {
+                Target::Variable(TargetVariable::AllEntities | TargetVariable::Executor) => {
+                    !options.entity_type.iter().any(|entity_type| !entity_type.negated)
+                }
+                _ => false,
+            };
+            if !applicable {
+                reader.set_cursor(start);
+                return Err(reader.error("Option 'type' isn't applicable here"));
+            }
+            let negated = read_negatable(reader);
+            if !negated && (!options.entity_type.is_empty() || !options.type_tag.is_empty()) {
+                reader.set_cursor(start);
+                return Err(reader.error("Option 'type' isn't applicable here"));
+            }
+            if reader.peek() == Some('#') {
+                reader.skip();
+                let value = reader.read_resource_location()?.to_string();
+                options.type_tag.push(Negatable { value, negated });
+            } else {
+                let value = reader.read_resource_location()?.to_string();
+                options.entity_type.push(Negatable { value, negated });
+            }
+        }
+        "tag" => {
+            let negated = read_negatable(reader);
+            let value = reader.read_unquoted_string().to_string();
+            options.tag.push(Negatable { value, negated });
+        }
+        "nbt" => {
+            let negated = read_negatable(reader);
+            let value = read_compound(reader)?;
+            options.nbt.push(Negatable { value, negated });
+        }
+        "scores" if options.scores.is_empty() => {
+            options.scores = read_map(reader, MinMaxBounds::<i32>::read)?;
+        }
+        "advancements" if options.advancements.is_empty() => {
+            options.advancements = read_map(reader, |reader| {
+                if reader.peek() == Some('{') {
+                    Ok(AdvancementPredicate::Criteria(read_map(reader, |reader| reader.read_boolean())?))
+                } else {
+                    Ok(AdvancementPredicate::Done(reader.read_boolean()?))
+                }
+            })?;
+        }
+        "predicate" => {
+            let negated = read_negatable(reader);
+            let value = reader.read_resource_location()?.to_string();
+            options.predicate.push(Negatable { value, negated });
+        }
+        "limit" | "sort" | "scores" | "advancements" => {
+            reader.set_cursor(start);
+            return Err(reader.error(format!("Option '{}' isn't applicable here", option)));
+        }
+        _ => {
+            reader.set_cursor(start);
+            return Err(reader.error(format!("Unknown option '{}'", option)));
+        }
+    }
+    Ok(())
+}
+
+/// Reads a selector, player name or UUID without validating it against node properties.
+pub fn read_selector(reader: &mut StringReader) -> anyhow::Result<EntitySelector> {
+    let start = reader.cursor();
+    if reader.peek() != Some('@') {
+        let name = reader.read_string()?;
+        if let Some(uuid) = parse_uuid(&name) {
+            return Ok(EntitySelector::new(Target::Uuid(uuid), SelectorOptions::default()));
+        }
+        if name.is_empty() || name.len() > 16 {
+            reader.set_cursor(start);
+            return Err(reader.error("Invalid name or UUID"));
+        }
+        return Ok(EntitySelector::new(Target::Player(name), SelectorOptions::default()));
+    }
+
+    reader.skip();
+    let variable = match reader.read() {
+        Some('p') => TargetVariable::NearestPlayer,
+        Some('r') => TargetVariable::RandomPlayer,
+        Some('a') => TargetVariable::AllPlayers,
+        Some('e') => TargetVariable::AllEntities,
+        Some('s') => TargetVariable::Executor,
+        _ => {
+            reader.set_cursor(start);
+            return Err(reader.error("Unknown selector type"));
+        }
+    };
+    let target = Target::Variable(variable);
+    let mut options = SelectorOptions::default();
+    if reader.peek() == Some('[') {
+        reader.skip();
+        reader.skip_whitespace();
+        while reader.can_read() && reader.peek() != Some(']') {
+            reader.skip_whitespace();
+            read_option(reader, &target, &mut options)?;
+            reader.skip_whitespace();
+            match reader.peek() {
+                Some(',') => reader.skip(),
+                Some(']') => {}
+                _ => return Err(reader.error("Expected end of options")),
+            }
+        }
+        reader.expect(']')?;
+    }
+    Ok(EntitySelector::new(target, options))
+}
+
+/// Reads a selector and validates it against the `minecraft:entity` properties of a node.
+pub fn read_entity(reader: &mut StringReader, bits: &SelectorBits) -> anyhow::Result<EntitySelector> {
+    let start = reader.cursor();
+    let selector = read_selector(reader)?;
+    if let Err(error) = selector.validate(bits) {
+        reader.set_cursor(start);
+        return Err(reader.error(error.to_string()));
+    }
+    Ok(selector)
+}
+
+/// Parsed `minecraft:score_holder` argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreHolder {
+    Wildcard,
+    Selector(Box<EntitySelector>),
+    Name(String),
+}
+
+pub fn read_score_holder(reader: &mut StringReader, bits: &ScoreHolderSelector) -> anyhow::Result<ScoreHolder> {
+    let start = reader.cursor();
+    match reader.peek() {
+        Some('@') => {
+            let selector = read_selector(reader)?;
+            if !bits.is_multiple() && selector.max_results() > 1 {
+                reader.set_cursor(start);
+                return Err(reader.error("Only one entity is allowed, but the provided selector allows more than one"));
+            }
+            Ok(ScoreHolder::Selector(Box::new(selector)))
+        }
+        Some('*') => {
+            reader.skip();
+            Ok(ScoreHolder::Wildcard)
+        }
+        _ => {
+            let name = reader.read_while(|c| !c.is_whitespace());
+            if name.is_empty() {
+                return Err(reader.error("Expected a score holder"));
+            }
+            Ok(ScoreHolder::Name(name.to_string()))
+        }
+    }
+}
+
+/// Parsed `minecraft:entity_anchor` argument, the point of an entity to face or measure from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityAnchor {
+    Feet,
+    Eyes,
+}
+
+impl EntityAnchor {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntityAnchor::Feet => "feet",
+            EntityAnchor::Eyes => "eyes",
+        }
+    }
+
+    /// Offsets the feet position of an entity to the anchor.
+    pub fn apply(&self, position: (f64, f64, f64), eye_height: f64) -> (f64, f64, f64) {
+        match self {
+            EntityAnchor::Feet => position,
+            EntityAnchor::Eyes => (position.0, position.1 + eye_height, position.2),
+        }
+    }
+}
+
+pub fn read_entity_anchor(reader: &mut StringReader) -> anyhow::Result<EntityAnchor> {
+    let start = reader.cursor();
+    match reader.read_unquoted_string() {
+        "feet" => Ok(EntityAnchor::Feet),
+        "eyes" => Ok(EntityAnchor::Eyes),
+        anchor => {
+            reader.set_cursor(start);
+            Err(reader.error(format!("Invalid entity anchor {}", anchor)))
+        }
+    }
+}
+
+pub fn entity_parser<T>(bits: SelectorBits) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::Entity(Box::new(read_entity(reader, &bits)?))))
+}
+
+pub fn score_holder_parser<T>(bits: ScoreHolderSelector) -> ParserFunction<T> {
+    reader_parser(move |reader| Ok(Value::ScoreHolder(read_score_holder(reader, &bits)?)))
+}
+
+pub fn entity_anchor_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::EntityAnchor(read_entity_anchor(reader)?)))
+}
+
+pub fn entity_anchor_suggestions<T>() -> SuggestionFunction<T> {
+    fixed_suggestions(vec![EntityAnchor::Eyes.name().to_string(), EntityAnchor::Feet.name().to_string()])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::entity::{entity_parser, read_entity, read_score_holder, read_selector, EntitySelector as Selector, ScoreHolder, SelectorOptions, Sort, Target, TargetVariable};
+    use crate::arguments::parse_with_reader;
+    use crate::arguments::range::MinMaxBounds;
+    use crate::arguments::reader::StringReader;
+    use crate::executor::context::{CommandContext, Value};
+    use crate::protocol::{EntitySelector, ScoreHolderSelector};
+
+    #[test]
+    pub fn test_selector_options() -> anyhow::Result<()> {
+        let selector = read_selector(&mut StringReader::new("@e[distance=..10, type=!player,limit=3,sort=nearest,scores={kills=1..}]"))?;
+        assert_eq!(*selector.target(), Target::Variable(TargetVariable::AllEntities));
+        let options = selector.options();
+        assert_eq!(options.distance, Some(MinMaxBounds::new(None, Some(10.0))));
+        assert!(options.entity_type[0].negated && options.entity_type[0].value == "player");
+        assert_eq!(options.limit, Some(3));
+        assert_eq!(selector.sort(), Sort::Nearest);
+        assert_eq!(options.scores, vec![("kills".to_string(), MinMaxBounds::new(Some(1), None))]);
+        assert_eq!(selector.max_results(), 3);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parser_spans_arguments() -> anyhow::Result<()> {
+        let parser = entity_parser::<()>(EntitySelector::new(false, false));
+        let (value, context) = parser(CommandContext::create((), vec!["@e[type=!player,", "limit=1]", "rest"]))?;
+        assert!(matches!(value, Value::Entity(selector) if selector.options().limit == Some(1)));
+        assert_eq!(context.args().to_vec(), vec!["rest".to_string()]);
+        assert!(parser(CommandContext::create((), vec!["@e[limit=1]x"])).is_err());
+
+        let (_, context) = parse_with_reader(CommandContext::<()>::create((), vec!["@p", ""]), |reader| {
+            let selector = read_selector(reader)?;
+            reader.skip();
+            Ok(selector)
+        })?;
+        assert_eq!(context.args().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_names_and_uuids() -> anyhow::Result<()> {
+        assert_eq!(*read_selector(&mut StringReader::new("Notch"))?.target(), Target::Player("Notch".into()));
+        assert_eq!(
+            *read_selector(&mut StringReader::new("f81d4fae-7dec-11d0-a765-00a0c91e6bf6"))?.target(),
+            Target::Uuid(0xf81d4fae_7dec_11d0_a765_00a0c91e6bf6)
+        );
+        assert!(read_selector(&mut StringReader::new("ThisNameIsFarTooLong")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_validation() {
+        let single = EntitySelector::new(true, false);
+        let players = EntitySelector::new(false, true);
+        assert!(read_entity(&mut StringReader::new("@a"), &single).is_err());
+        assert!(read_entity(&mut StringReader::new("@a[limit=1]"), &single).is_ok());
+        assert!(read_entity(&mut StringReader::new("@e"), &players).is_err());
+        assert!(read_entity(&mut StringReader::new("@e[type=player]"), &players).is_ok());
+        assert!(read_entity(&mut StringReader::new("@s"), &players).is_ok());
+        assert!(read_selector(&mut StringReader::new("@a[type=cow]")).is_err());
+        assert!(read_selector(&mut StringReader::new("@s[limit=2]")).is_err());
+        assert!(read_selector(&mut StringReader::new("@e[limit=-1]")).is_err());
+        let negative = Selector::new(Target::Variable(TargetVariable::AllEntities), SelectorOptions { limit: Some(-1), ..Default::default() });
+        assert_eq!(negative.max_results(), 0);
+        assert!(read_selector(&mut StringReader::new("@e[unknown=1]")).is_err());
+        assert_eq!(
+            read_score_holder(&mut StringReader::new("*"), &ScoreHolderSelector::new(false)).ok(),
+            Some(ScoreHolder::Wildcard)
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::arguments::block::ResourceTarget;
+use crate::arguments::reader::StringReader;
+use crate::arguments::reader_parser;
+use crate::executor::command::{ParserFunction, SuggestionFunction};
+use crate::executor::context::Value;
+use crate::protocol::Parser;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// Namespaced identifier such as `minecraft:speed`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceLocation {
+    namespace: String,
+    path: String,
+}
+
+fn is_allowed_in_namespace(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-' || c == '.'
+}
+
+fn is_allowed_in_path(c: char) -> bool {
+    is_allowed_in_namespace(c) || c == '/'
+}
+
+impl ResourceLocation {
+    pub fn new<N: Into<String>, P: Into<String>>(namespace: N, path: P) -> anyhow::Result<Self> {
+        let (namespace, path) = (namespace.into(), path.into());
+        if !namespace.chars().all(is_allowed_in_namespace) {
+            anyhow::bail!("Non [a-z0-9_.-] character in namespace of location: {}:{}", namespace, path);
+        }
+        if !path.chars().all(is_allowed_in_path) {
+            anyhow::bail!("Non [a-z0-9/._-] character in path of location: {}:{}", namespace, path);
+        }
+        Ok(Self { namespace, path })
+    }
+
+    /// Parses `namespace:path`, or a bare `path` in the default namespace if one is given.
+    pub fn parse(location: &str, default_namespace: Option<&str>) -> anyhow::Result<Self> {
+        match (location.split_once(':'), default_namespace) {
+            (Some((namespace, path)), _) => Self::new(namespace, path),
+            (None, Some(namespace)) => Self::new(namespace, location),
+            (None, None) => anyhow::bail!("Expected a namespace in location: {}", location),
+        }
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Display for ResourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+/// Registries identifier arguments validate against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RegistryKind {
+    MobEffect,
+    Enchantment,
+    EntityType,
+    Dimension,
+    Particle,
+    Function,
+    FunctionTag,
+    Objective,
+    Custom(String),
+}
+
+impl RegistryKind {
+    fn unknown(&self, entry: &str) -> String {
+        match self {
+            RegistryKind::MobEffect => format!("Unknown effect: {}", entry),
+            RegistryKind::Enchantment => format!("Unknown enchantment: {}", entry),
+            RegistryKind::EntityType => format!("Unknown entity: {}", entry),
+            RegistryKind::Dimension => format!("Unknown dimension '{}'", entry),
+            RegistryKind::Particle => format!("Unknown particle: {}", entry),
+            RegistryKind::Function => format!("Unknown function {}", entry),
+            RegistryKind::FunctionTag => format!("Unknown function tag {}", entry),
+            RegistryKind::Objective => format!("Unknown scoreboard objective '{}'", entry),
+            RegistryKind::Custom(name) => format!("Unknown {}: {}", name, entry),
+        }
+    }
+}
+
+/// Live registries of the host server, e.g. the loaded effects, dimensions or functions.
+pub trait Registry: Send + Sync {
+    /// Every entry of the registry; identifiers are namespaced, objectives are plain names.
+    fn entries(&self, kind: &RegistryKind) -> Vec<String>;
+
+    fn contains(&self, kind: &RegistryKind, entry: &str) -> bool {
+        self.entries(kind).iter().any(|known| known == entry)
+    }
+}
+
+/// Configures how an identifier argument is read, validated and suggested.
+#[derive(Clone)]
+pub struct IdentifierArgument {
+    kind: Option<RegistryKind>,
+    default_namespace: Option<String>,
+    registry: Option<Arc<dyn Registry>>,
+}
+
+impl IdentifierArgument {
+    /// Identifier of the given registry, defaulting to the `minecraft` namespace.
+    pub fn new(kind: RegistryKind) -> Self {
+        Self { kind: Some(kind), default_namespace: Some("minecraft".into()), registry: None }
+    }
+
+    /// Identifier which isn't tied to a registry, as read by `minecraft:resource_location`.
+    pub fn resource_location() -> Self {
+        Self { kind: None, default_namespace: Some("minecraft".into()), registry: None }
+    }
+
+    /// Picks the registry backing a protocol parser, if it reads an identifier.
+    pub fn for_parser(parser: &Parser) -> Option<Self> {
+        let kind = match parser {
+            Parser::ResourceLocation => return Some(Self::resource_location()),
+            Parser::MobEffect => RegistryKind::MobEffect,
+            Parser::ItemEnchantment => RegistryKind::Enchantment,
+            Parser::EntitySummon => RegistryKind::EntityType,
+            Parser::Dimension => RegistryKind::Dimension,
+            Parser::Particle => RegistryKind::Particle,
+            Parser::Function => RegistryKind::Function,
+            Parser::Objective => RegistryKind::Objective,
+            _ => return None,
+        };
+        Some(Self::new(kind))
+    }
+
+    /// Sets the namespace of bare identifiers; without one a namespace must be typed.
+    pub fn with_default_namespace<S: Into<String>>(mut self, default_namespace: Option<S>) -> Self {
+        self.default_namespace = default_namespace.map(Into::into);
+        self
+    }
+
+    pub fn with_registry(mut self, registry: Arc<dyn Registry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn kind(&self) -> Option<&RegistryKind> {
+        self.kind.as_ref()
+    }
+
+    fn validate(&self, reader: &mut StringReader, start: usize, kind: &RegistryKind, entry: &str) -> anyhow::Result<()> {
+        if let Some(registry) = &self.registry {
+            if !registry.contains(kind, entry) {
+                reader.set_cursor(start);
+                return Err(reader.error(kind.unknown(entry)));
+            }
+        }
+        Ok(())
+    }
+
+    fn read_location(&self, reader: &mut StringReader) -> anyhow::Result<ResourceLocation> {
+        let start = reader.cursor();
+        let location = reader.read_resource_location()?;
+        ResourceLocation::parse(location, self.default_namespace.as_deref()).map_err(|error| {
+            reader.set_cursor(start);
+            reader.error(error.to_string())
+        })
+    }
+
+    /// Reads and validates the argument; functions may also name a `#` tag.
+    pub fn read(&self, reader: &mut StringReader) -> anyhow::Result<Value> {
+        let start = reader.cursor();
+        match &self.kind {
+            Some(RegistryKind::Objective) => {
+                let name = reader.read_unquoted_string();
+                if name.is_empty() {
+                    return Err(reader.error("Expected objective"));
+                }
+                self.validate(reader, start, &RegistryKind::Objective, name)?;
+                Ok(Value::String(name.to_string()))
+            }
+            Some(RegistryKind::Function) if reader.peek() == Some('#') => {
+                reader.skip();
+                let location = self.read_location(reader)?;
+                self.validate(reader, start, &RegistryKind::FunctionTag, &location.to_string())?;
+                Ok(Value::Function(ResourceTarget::Tag(location.to_string())))
+            }
+            Some(RegistryKind::Function) => {
+                let location = self.read_location(reader)?;
+                self.validate(reader, start, &RegistryKind::Function, &location.to_string())?;
+                Ok(Value::Function(ResourceTarget::Id(location.to_string())))
+            }
+            Some(kind) => {
+                let location = self.read_location(reader)?;
+                self.validate(reader, start, kind, &location.to_string())?;
+                Ok(Value::Identifier(location))
+            }
+            None => Ok(Value::Identifier(self.read_location(reader)?)),
+        }
+    }
+
+    /// Registry entries matching the partial argument, typed either in full or without the
+    /// default namespace.
+    pub fn suggest(&self, partial: &str) -> Vec<String> {
+        let (registry, kind) = match (&self.registry, &self.kind) {
+            (Some(registry), Some(kind)) => (registry, kind),
+            _ => return Vec::new(),
+        };
+        let mut candidates = registry.entries(kind);
+        if *kind == RegistryKind::Function {
+            candidates.extend(registry.entries(&RegistryKind::FunctionTag).into_iter().map(|tag| format!("#{}", tag)));
+        }
+        let default_prefix = self.default_namespace.as_ref().map(|namespace| format!("{}:", namespace));
+        let mut suggestions = candidates
+            .into_iter()
+            .filter(|candidate| {
+                let (tag, location) = match candidate.strip_prefix('#') {
+                    Some(location) => ("#", location),
+                    None => ("", candidate.as_str()),
+                };
+                let short = default_prefix.as_ref().and_then(|prefix| location.strip_prefix(prefix.as_str()));
+                candidate.starts_with(partial) || short.is_some_and(|short| format!("{}{}", tag, short).starts_with(partial))
+            })
+            .collect::<Vec<String>>();
+        suggestions.sort();
+        suggestions
+    }
+
+    pub fn parser<T>(&self) -> ParserFunction<T> {
+        let argument = self.clone();
+        reader_parser(move |reader| argument.read(reader))
+    }
+
+    pub fn suggestions<T>(&self) -> SuggestionFunction<T> {
+        let argument = self.clone();
+        Box::new(move |_, partial| argument.suggest(partial))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::block::ResourceTarget;
+    use crate::arguments::identifier::{IdentifierArgument, Registry, RegistryKind, ResourceLocation};
+    use crate::arguments::reader::StringReader;
+    use crate::executor::context::Value;
+    use std::sync::Arc;
+
+    struct Server;
+
+    impl Registry for Server {
+        fn entries(&self, kind: &RegistryKind) -> Vec<String> {
+            match kind {
+                RegistryKind::MobEffect => vec!["minecraft:speed".into(), "minecraft:slowness".into(), "magic:flight".into()],
+                RegistryKind::Function => vec!["magic:setup".into()],
+                RegistryKind::FunctionTag => vec!["minecraft:tick".into()],
+                RegistryKind::Objective => vec!["kills".into()],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_identifiers() -> anyhow::Result<()> {
+        let effect = IdentifierArgument::new(RegistryKind::MobEffect).with_registry(Arc::new(Server));
+        match effect.read(&mut StringReader::new("speed"))? {
+            Value::Identifier(location) => assert_eq!(location, ResourceLocation::new("minecraft", "speed")?),
+            other => panic!("unexpected value {:?}", other),
+        }
+        assert!(effect.read(&mut StringReader::new("magic:flight")).is_ok());
+        assert!(effect.read(&mut StringReader::new("haste")).is_err());
+        assert!(effect.clone().with_default_namespace(None::<String>).read(&mut StringReader::new("speed")).is_err());
+        assert!(IdentifierArgument::resource_location().read(&mut StringReader::new("a:b:c")).is_err());
+
+        let function = IdentifierArgument::new(RegistryKind::Function).with_registry(Arc::new(Server));
+        assert!(matches!(function.read(&mut StringReader::new("#tick"))?, Value::Function(ResourceTarget::Tag(tag)) if tag == "minecraft:tick"));
+        assert!(IdentifierArgument::new(RegistryKind::Objective).with_registry(Arc::new(Server)).read(&mut StringReader::new("deaths")).is_err());
+
+        assert_eq!(effect.suggest("s"), vec!["minecraft:slowness", "minecraft:speed"]);
+        assert_eq!(effect.suggest("magic"), vec!["magic:flight"]);
+        assert_eq!(function.suggest("#"), vec!["#minecraft:tick"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::arguments::reader::StringReader;
+use crate::arguments::{fixed_suggestions, reader_parser};
+use crate::executor::command::{ParserFunction, SuggestionFunction};
+use crate::executor::context::Value;
+
+/// Chat colors accepted by `minecraft:color`, plus `reset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    Reset,
+}
+
+impl Color {
+    pub const ALL: [Color; 17] = [
+        Color::Black,
+        Color::DarkBlue,
+        Color::DarkGreen,
+        Color::DarkAqua,
+        Color::DarkRed,
+        Color::DarkPurple,
+        Color::Gold,
+        Color::Gray,
+        Color::DarkGray,
+        Color::Blue,
+        Color::Green,
+        Color::Aqua,
+        Color::Red,
+        Color::LightPurple,
+        Color::Yellow,
+        Color::White,
+        Color::Reset,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Color::Black => "black",
+            Color::DarkBlue => "dark_blue",
+            Color::DarkGreen => "dark_green",
+            Color::DarkAqua => "dark_aqua",
+            Color::DarkRed => "dark_red",
+            Color::DarkPurple => "dark_purple",
+            Color::Gold => "gold",
+            Color::Gray => "gray",
+            Color::DarkGray => "dark_gray",
+            Color::Blue => "blue",
+            Color::Green => "green",
+            Color::Aqua => "aqua",
+            Color::Red => "red",
+            Color::LightPurple => "light_purple",
+            Color::Yellow => "yellow",
+            Color::White => "white",
+            Color::Reset => "reset",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Color> {
+        Color::ALL.iter().copied().find(|color| color.name() == name)
+    }
+}
+
+pub fn read_color(reader: &mut StringReader) -> anyhow::Result<Color> {
+    let start = reader.cursor();
+    let name = reader.read_unquoted_string();
+    Color::from_name(name).ok_or_else(|| {
+        reader.set_cursor(start);
+        reader.error(format!("Invalid color '{}'", name))
+    })
+}
+
+/// Reads a duration such as `1d`, `20t`, `3s` or `5` into game ticks.
+pub fn read_time(reader: &mut StringReader) -> anyhow::Result<u32> {
+    let value = reader.read_float()?;
+    let start = reader.cursor();
+    let factor = match reader.read_unquoted_string() {
+        "d" => 24000.0,
+        "s" => 20.0,
+        "t" | "" => 1.0,
+        unit => {
+            reader.set_cursor(start);
+            return Err(reader.error(format!("Invalid unit '{}'", unit)));
+        }
+    };
+    let ticks = (value * factor).round();
+    if ticks < 0.0 {
+        reader.set_cursor(start);
+        return Err(reader.error("Tick count must be non-negative"));
+    }
+    Ok(ticks as u32)
+}
+
+/// Every name accepted by `minecraft:item_slot` with its inventory slot index.
+pub fn item_slots() -> Vec<(String, i32)> {
+    let mut slots = Vec::new();
+    let ranges = [("container", 0, 54), ("hotbar", 0, 9), ("inventory", 9, 27), ("enderchest", 200, 27), ("villager", 300, 8), ("horse", 500, 15)];
+    for (name, offset, count) in ranges {
+        slots.extend((0..count).map(|index| (format!("{}.{}", name, index), offset + index)));
+    }
+    let named = [
+        ("weapon", 98),
+        ("weapon.mainhand", 98),
+        ("weapon.offhand", 99),
+        ("armor.head", 103),
+        ("armor.chest", 102),
+        ("armor.legs", 101),
+        ("armor.feet", 100),
+        ("horse.saddle", 400),
+        ("horse.armor", 401),
+        ("horse.chest", 499),
+    ];
+    slots.extend(named.iter().map(|(name, index)| (name.to_string(), *index)));
+    slots
+}
+
+/// Reads a slot name such as `container.5` or `weapon.mainhand` into its slot index.
+pub fn read_item_slot(reader: &mut StringReader) -> anyhow::Result<i32> {
+    let start = reader.cursor();
+    let name = reader.read_unquoted_string();
+    match item_slots().into_iter().find(|(slot, _)| slot == name) {
+        Some((_, index)) => Ok(index),
+        None => {
+            reader.set_cursor(start);
+            Err(reader.error(format!("Unknown slot '{}'", name)))
+        }
+    }
+}
+
+pub fn color_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Color(read_color(reader)?)))
+}
+
+pub fn time_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Time(read_time(reader)?)))
+}
+
+pub fn item_slot_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::ItemSlot(read_item_slot(reader)?)))
+}
+
+pub fn color_suggestions<T>() -> SuggestionFunction<T> {
+    fixed_suggestions(Color::ALL.iter().map(|color| color.name().to_string()).collect())
+}
+
+/// Suggests the time units once a number has been typed.
+pub fn time_suggestions<T>() -> SuggestionFunction<T> {
+    Box::new(|_, partial| {
+        if partial.is_empty() || partial.parse::<f32>().is_err() {
+            return Vec::new();
+        }
+        ["d", "s", "t"].iter().map(|unit| format!("{}{}", partial, unit)).collect()
+    })
+}
+
+pub fn item_slot_suggestions<T>() -> SuggestionFunction<T> {
+    fixed_suggestions(item_slots().into_iter().map(|(name, _)| name).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::misc::{read_color, read_item_slot, read_time, Color};
+    use crate::arguments::reader::StringReader;
+
+    #[test]
+    pub fn test_misc_arguments() -> anyhow::Result<()> {
+        assert_eq!(read_time(&mut StringReader::new("1d"))?, 24000);
+        assert_eq!(read_time(&mut StringReader::new("1.5s"))?, 30);
+        assert_eq!(read_time(&mut StringReader::new("20"))?, 20);
+        assert!(read_time(&mut StringReader::new("3m")).is_err());
+        assert!(read_time(&mut StringReader::new("-1t")).is_err());
+
+        assert_eq!(read_item_slot(&mut StringReader::new("container.5"))?, 5);
+        assert_eq!(read_item_slot(&mut StringReader::new("inventory.0"))?, 9);
+        assert_eq!(read_item_slot(&mut StringReader::new("weapon.offhand"))?, 99);
+        assert!(read_item_slot(&mut StringReader::new("hotbar.9")).is_err());
+
+        assert_eq!(read_color(&mut StringReader::new("light_purple"))?, Color::LightPurple);
+        assert!(read_color(&mut StringReader::new("pink")).is_err());
+        Ok(())
+    }
+}
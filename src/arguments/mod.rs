@@ -0,0 +1,66 @@
+use crate::arguments::reader::StringReader;
+use crate::executor::command::{ParserFunction, SuggestionFunction};
+use crate::executor::context::{CommandContext, Value};
+
+pub mod reader;
+pub mod range;
+pub mod entity;
+pub mod coordinates;
+pub mod nbt;
+pub mod block;
+pub mod chat;
+pub mod misc;
+pub mod scoreboard;
+pub mod identifier;
+pub mod profile;
+
+/// Runs a reader based parser against the remaining arguments of the context.
+///
+/// Arguments are rejoined with single spaces, so a parser may span several arguments as long as
+/// it stops on an argument boundary. Consumed arguments are trimmed from the returned context.
+pub fn parse_with_reader<T, V, F>(mut context: CommandContext<T>, parse: F) -> anyhow::Result<(V, CommandContext<T>)>
+where
+    F: FnOnce(&mut StringReader) -> anyhow::Result<V>,
+{
+    let (value, consumed) = {
+        let mut reader = StringReader::new(context.args().rest());
+        let value = parse(&mut reader)?;
+        if reader.can_read() && reader.peek() != Some(' ') {
+            return Err(reader.error("Expected whitespace to end one argument, but found trailing data"));
+        }
+        let consumed = context.args().count_before(reader.cursor());
+        (value, consumed)
+    };
+    for _ in 0..consumed {
+        context.trim_top();
+    }
+    Ok((value, context))
+}
+
+/// Boxes a reader based parser so it can be bound to an `ArgParserCommand`.
+pub fn reader_parser<T, F>(parse: F) -> ParserFunction<T>
+where
+    F: Fn(&mut StringReader) -> anyhow::Result<Value> + Send + Sync + 'static,
+{
+    Box::new(move |context| parse_with_reader(context, |reader| parse(reader)))
+}
+
+/// Filters candidates down to those starting with the partial argument, sorted.
+pub fn suggest_matching<I, S>(partial: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut suggestions = candidates
+        .into_iter()
+        .filter(|candidate| candidate.as_ref().starts_with(partial))
+        .map(|candidate| candidate.as_ref().to_string())
+        .collect::<Vec<String>>();
+    suggestions.sort();
+    suggestions
+}
+
+/// Boxes a fixed set of candidates as a suggestion function.
+pub fn fixed_suggestions<T>(candidates: Vec<String>) -> SuggestionFunction<T> {
+    Box::new(move |_, partial| suggest_matching(partial, &candidates))
+}
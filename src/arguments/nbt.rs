@@ -0,0 +1,593 @@
+use crate::arguments::reader::{is_allowed_in_unquoted_string, StringReader};
+use crate::arguments::reader_parser;
+use crate::executor::command::{ParserFunction, SuggestionFunction};
+use crate::executor::context::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+pub type NbtCompound = BTreeMap<String, NbtTag>;
+
+/// Typed NBT tree read from SNBT, e.g. `{Count:1b,tag:{display:{Name:'"Sword"'}}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<NbtTag>),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl NbtTag {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            NbtTag::Byte(_) => "TAG_Byte",
+            NbtTag::Short(_) => "TAG_Short",
+            NbtTag::Int(_) => "TAG_Int",
+            NbtTag::Long(_) => "TAG_Long",
+            NbtTag::Float(_) => "TAG_Float",
+            NbtTag::Double(_) => "TAG_Double",
+            NbtTag::ByteArray(_) => "TAG_Byte_Array",
+            NbtTag::String(_) => "TAG_String",
+            NbtTag::List(_) => "TAG_List",
+            NbtTag::Compound(_) => "TAG_Compound",
+            NbtTag::IntArray(_) => "TAG_Int_Array",
+            NbtTag::LongArray(_) => "TAG_Long_Array",
+        }
+    }
+
+    fn same_type(&self, other: &NbtTag) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, value: &str) -> std::fmt::Result {
+    let quote = if value.contains('"') && !value.contains('\'') { '\'' } else { '"' };
+    write!(f, "{}", quote)?;
+    for c in value.chars() {
+        if c == quote || c == '\\' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    write!(f, "{}", quote)
+}
+
+fn write_key(f: &mut Formatter<'_>, key: &str) -> std::fmt::Result {
+    if !key.is_empty() && key.chars().all(is_allowed_in_unquoted_string) {
+        write!(f, "{}", key)
+    } else {
+        write_string(f, key)
+    }
+}
+
+fn write_elements<V, F>(f: &mut Formatter<'_>, prefix: &str, elements: &[V], mut write: F) -> std::fmt::Result
+where
+    F: FnMut(&mut Formatter<'_>, &V) -> std::fmt::Result,
+{
+    write!(f, "[{}", prefix)?;
+    for (index, element) in elements.iter().enumerate() {
+        if index > 0 {
+            write!(f, ",")?;
+        }
+        write(f, element)?;
+    }
+    write!(f, "]")
+}
+
+impl Display for NbtTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NbtTag::Byte(value) => write!(f, "{}b", value),
+            NbtTag::Short(value) => write!(f, "{}s", value),
+            NbtTag::Int(value) => write!(f, "{}", value),
+            NbtTag::Long(value) => write!(f, "{}L", value),
+            NbtTag::Float(value) => write!(f, "{}f", value),
+            NbtTag::Double(value) => write!(f, "{}d", value),
+            NbtTag::ByteArray(values) => write_elements(f, "B;", values, |f, value| write!(f, "{}b", value)),
+            NbtTag::String(value) => write_string(f, value),
+            NbtTag::List(values) => write_elements(f, "", values, |f, value| write!(f, "{}", value)),
+            NbtTag::Compound(compound) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in compound.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_key(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+            NbtTag::IntArray(values) => write_elements(f, "I;", values, |f, value| write!(f, "{}", value)),
+            NbtTag::LongArray(values) => write_elements(f, "L;", values, |f, value| write!(f, "{}L", value)),
+        }
+    }
+}
+
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix(|c| c == '-' || c == '+').unwrap_or(value);
+    digits == "0" || (digits.starts_with(|c: char| ('1'..='9').contains(&c)) && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_decimal(value: &str, dot_required: bool) -> bool {
+    let unsigned = value.strip_prefix(|c| c == '-' || c == '+').unwrap_or(value);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (&unsigned[..index], Some(&unsigned[index + 1..])),
+        None => (unsigned, None),
+    };
+    if let Some(exponent) = exponent {
+        let exponent = exponent.strip_prefix(|c| c == '-' || c == '+').unwrap_or(exponent);
+        if exponent.is_empty() || !exponent.chars().all(|c| c.is_ascii_digit()) {
+            return false;
+        }
+    }
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    match mantissa.split_once('.') {
+        Some((whole, fraction)) => all_digits(whole) && all_digits(fraction) && !(whole.is_empty() && fraction.is_empty()),
+        None => !dot_required && !mantissa.is_empty() && all_digits(mantissa),
+    }
+}
+
+/// Types an unquoted SNBT value the same way vanilla does, falling back to a string.
+fn type_unquoted(value: &str) -> NbtTag {
+    let lower = value.to_ascii_lowercase();
+    let (body, suffix) = match lower.char_indices().last() {
+        Some((index, suffix)) => (&value[..index], Some(suffix)),
+        None => (value, None),
+    };
+    let typed = match suffix {
+        Some('f') if is_decimal(body, false) => body.parse().ok().map(NbtTag::Float),
+        Some('b') if is_integer(body) => body.parse().ok().map(NbtTag::Byte),
+        Some('l') if is_integer(body) => body.parse().ok().map(NbtTag::Long),
+        Some('s') if is_integer(body) => body.parse().ok().map(NbtTag::Short),
+        Some('d') if is_decimal(body, false) => body.parse().ok().map(NbtTag::Double),
+        _ => None,
+    };
+    if let Some(typed) = typed {
+        return typed;
+    }
+    if is_integer(value) {
+        if let Ok(value) = value.parse() {
+            return NbtTag::Int(value);
+        }
+    } else if is_decimal(value, true) {
+        if let Ok(value) = value.parse() {
+            return NbtTag::Double(value);
+        }
+    }
+    match lower.as_str() {
+        "true" => NbtTag::Byte(1),
+        "false" => NbtTag::Byte(0),
+        _ => NbtTag::String(value.to_string()),
+    }
+}
+
+fn has_element_separator(reader: &mut StringReader) -> bool {
+    reader.skip_whitespace();
+    if reader.peek() == Some(',') {
+        reader.skip();
+        reader.skip_whitespace();
+        true
+    } else {
+        false
+    }
+}
+
+fn read_key(reader: &mut StringReader) -> anyhow::Result<String> {
+    reader.skip_whitespace();
+    let start = reader.cursor();
+    let key = reader.read_string()?;
+    if reader.cursor() == start {
+        return Err(reader.error("Expected key"));
+    }
+    Ok(key)
+}
+
+pub fn read_compound(reader: &mut StringReader) -> anyhow::Result<NbtCompound> {
+    reader.skip_whitespace();
+    reader.expect('{')?;
+    let mut compound = NbtCompound::new();
+    reader.skip_whitespace();
+    while reader.can_read() && reader.peek() != Some('}') {
+        let key = read_key(reader)?;
+        reader.skip_whitespace();
+        reader.expect(':')?;
+        compound.insert(key, read_tag(reader)?);
+        if !has_element_separator(reader) {
+            break;
+        }
+        if !reader.can_read() {
+            return Err(reader.error("Expected key"));
+        }
+    }
+    reader.skip_whitespace();
+    reader.expect('}')?;
+    Ok(compound)
+}
+
+fn read_list(reader: &mut StringReader) -> anyhow::Result<NbtTag> {
+    reader.expect('[')?;
+    reader.skip_whitespace();
+    let mut values: Vec<NbtTag> = Vec::new();
+    while reader.can_read() && reader.peek() != Some(']') {
+        let start = reader.cursor();
+        let value = read_tag(reader)?;
+        if let Some(first) = values.first() {
+            if !first.same_type(&value) {
+                reader.set_cursor(start);
+                return Err(reader.error(format!("Can't insert {} into list of {}", value.type_name(), first.type_name())));
+            }
+        }
+        values.push(value);
+        if !has_element_separator(reader) {
+            break;
+        }
+        if !reader.can_read() {
+            return Err(reader.error("Expected value"));
+        }
+    }
+    reader.skip_whitespace();
+    reader.expect(']')?;
+    Ok(NbtTag::List(values))
+}
+
+fn read_array<V, F>(reader: &mut StringReader, array_type: &str, mut unwrap: F) -> anyhow::Result<Vec<V>>
+where
+    F: FnMut(NbtTag) -> Option<V>,
+{
+    let mut values = Vec::new();
+    reader.skip_whitespace();
+    while reader.can_read() && reader.peek() != Some(']') {
+        let start = reader.cursor();
+        let value = read_tag(reader)?;
+        let type_name = value.type_name();
+        match unwrap(value) {
+            Some(value) => values.push(value),
+            None => {
+                reader.set_cursor(start);
+                return Err(reader.error(format!("Can't insert {} into {}", type_name, array_type)));
+            }
+        }
+        if !has_element_separator(reader) {
+            break;
+        }
+        if !reader.can_read() {
+            return Err(reader.error("Expected value"));
+        }
+    }
+    reader.skip_whitespace();
+    reader.expect(']')?;
+    Ok(values)
+}
+
+fn read_list_or_array(reader: &mut StringReader) -> anyhow::Result<NbtTag> {
+    let is_array = reader.peek_at(2) == Some(';') && matches!(reader.peek_at(1), Some('B' | 'I' | 'L'));
+    if !is_array {
+        return read_list(reader);
+    }
+    reader.skip();
+    let array_type = reader.read();
+    reader.skip();
+    match array_type {
+        Some('B') => Ok(NbtTag::ByteArray(read_array(reader, "TAG_Byte_Array", |tag| match tag {
+            NbtTag::Byte(value) => Some(value),
+            _ => None,
+        })?)),
+        Some('I') => Ok(NbtTag::IntArray(read_array(reader, "TAG_Int_Array", |tag| match tag {
+            NbtTag::Int(value) => Some(value),
+            _ => None,
+        })?)),
+        _ => Ok(NbtTag::LongArray(read_array(reader, "TAG_Long_Array", |tag| match tag {
+            NbtTag::Long(value) => Some(value),
+            _ => None,
+        })?)),
+    }
+}
+
+/// Reads any SNBT value.
+pub fn read_tag(reader: &mut StringReader) -> anyhow::Result<NbtTag> {
+    reader.skip_whitespace();
+    match reader.peek() {
+        Some('{') => Ok(NbtTag::Compound(read_compound(reader)?)),
+        Some('[') => read_list_or_array(reader),
+        Some('"' | '\'') => Ok(NbtTag::String(reader.read_quoted_string()?)),
+        _ => {
+            let value = reader.read_unquoted_string();
+            if value.is_empty() {
+                return Err(reader.error("Expected value"));
+            }
+            Ok(type_unquoted(value))
+        }
+    }
+}
+
+/// Whether every entry of the filter is present in the tag, the way NBT path filters match.
+pub fn matches(filter: &NbtTag, tag: &NbtTag) -> bool {
+    match (filter, tag) {
+        (NbtTag::Compound(filter), NbtTag::Compound(tag)) => filter
+            .iter()
+            .all(|(key, filter)| tag.get(key).is_some_and(|tag| matches(filter, tag))),
+        (NbtTag::List(filter), NbtTag::List(tag)) => {
+            if filter.is_empty() {
+                tag.is_empty()
+            } else {
+                filter.iter().all(|filter| tag.iter().any(|tag| matches(filter, tag)))
+            }
+        }
+        (filter, tag) => filter == tag,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtPathNode {
+    MatchRoot(NbtCompound),
+    Child(String),
+    MatchChild(String, NbtCompound),
+    AllElements,
+    Index(i32),
+    MatchElement(NbtCompound),
+}
+
+/// Parsed `minecraft:nbt_path` argument, e.g. `Items[0].tag.display.Name` or `Items[{Count:1b}]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtPath {
+    original: String,
+    nodes: Vec<NbtPathNode>,
+}
+
+fn is_allowed_in_path_key(c: char) -> bool {
+    !matches!(c, ' ' | '"' | '\'' | '[' | ']' | '.' | '{' | '}')
+}
+
+fn element_count(tag: &NbtTag) -> Option<usize> {
+    match tag {
+        NbtTag::List(values) => Some(values.len()),
+        NbtTag::ByteArray(values) => Some(values.len()),
+        NbtTag::IntArray(values) => Some(values.len()),
+        NbtTag::LongArray(values) => Some(values.len()),
+        _ => None,
+    }
+}
+
+fn element(tag: &NbtTag, index: usize) -> Option<NbtTag> {
+    match tag {
+        NbtTag::List(values) => values.get(index).cloned(),
+        NbtTag::ByteArray(values) => values.get(index).copied().map(NbtTag::Byte),
+        NbtTag::IntArray(values) => values.get(index).copied().map(NbtTag::Int),
+        NbtTag::LongArray(values) => values.get(index).copied().map(NbtTag::Long),
+        _ => None,
+    }
+}
+
+impl NbtPathNode {
+    fn select(&self, tag: &NbtTag, selected: &mut Vec<NbtTag>) {
+        match (self, tag) {
+            (NbtPathNode::MatchRoot(filter), tag) if matches(&NbtTag::Compound(filter.clone()), tag) => {
+                selected.push(tag.clone());
+            }
+            (NbtPathNode::Child(key), NbtTag::Compound(compound)) => selected.extend(compound.get(key).cloned()),
+            (NbtPathNode::MatchChild(key, filter), NbtTag::Compound(compound)) => {
+                let filter = NbtTag::Compound(filter.clone());
+                selected.extend(compound.get(key).filter(|child| matches(&filter, child)).cloned());
+            }
+            (NbtPathNode::AllElements, tag) => {
+                let count = element_count(tag).unwrap_or(0);
+                selected.extend((0..count).filter_map(|index| element(tag, index)));
+            }
+            (NbtPathNode::Index(index), tag) => {
+                if let Some(count) = element_count(tag) {
+                    let index = if *index < 0 { count as i64 + *index as i64 } else { *index as i64 };
+                    if index >= 0 {
+                        selected.extend(element(tag, index as usize));
+                    }
+                }
+            }
+            (NbtPathNode::MatchElement(filter), NbtTag::List(values)) => {
+                let filter = NbtTag::Compound(filter.clone());
+                selected.extend(values.iter().filter(|value| matches(&filter, value)).cloned());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl NbtPath {
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    pub fn nodes(&self) -> &Vec<NbtPathNode> {
+        &self.nodes
+    }
+
+    /// Collects every tag the path points at within the root tag.
+    pub fn get(&self, root: &NbtTag) -> Vec<NbtTag> {
+        let mut current = vec![root.clone()];
+        for node in &self.nodes {
+            let mut selected = Vec::new();
+            for tag in &current {
+                node.select(tag, &mut selected);
+            }
+            current = selected;
+        }
+        current
+    }
+
+    /// Suggests completions for the last segment of a partial path, using the keys found in the
+    /// data the path will be applied to.
+    pub fn suggest(partial: &str, data: &NbtTag) -> Vec<String> {
+        let (prefix, segment) = match partial.rfind('.') {
+            Some(index) => (&partial[..=index], &partial[index + 1..]),
+            None => ("", partial),
+        };
+        let parents = if prefix.is_empty() {
+            vec![data.clone()]
+        } else {
+            match read_nbt_path(&mut StringReader::new(&prefix[..prefix.len() - 1])) {
+                Ok(path) => path.get(data),
+                Err(_) => return Vec::new(),
+            }
+        };
+        let mut suggestions = parents
+            .iter()
+            .filter_map(|parent| match parent {
+                NbtTag::Compound(compound) => Some(compound.keys()),
+                _ => None,
+            })
+            .flatten()
+            .filter(|key| key.starts_with(segment))
+            .map(|key| {
+                if !key.is_empty() && key.chars().all(is_allowed_in_path_key) {
+                    format!("{}{}", prefix, key)
+                } else {
+                    format!("{}{}", prefix, NbtTag::String(key.clone()))
+                }
+            })
+            .collect::<Vec<String>>();
+        suggestions.sort();
+        suggestions.dedup();
+        suggestions
+    }
+}
+
+fn read_path_key(reader: &mut StringReader) -> anyhow::Result<NbtPathNode> {
+    let key = match reader.peek() {
+        Some('"' | '\'') => reader.read_quoted_string()?,
+        _ => {
+            let key = reader.read_while(is_allowed_in_path_key);
+            if key.is_empty() {
+                return Err(reader.error("Invalid NBT path element"));
+            }
+            key.to_string()
+        }
+    };
+    if reader.peek() == Some('{') {
+        Ok(NbtPathNode::MatchChild(key, read_compound(reader)?))
+    } else {
+        Ok(NbtPathNode::Child(key))
+    }
+}
+
+fn read_path_node(reader: &mut StringReader, first: bool) -> anyhow::Result<NbtPathNode> {
+    match reader.peek() {
+        Some('{') => {
+            if !first {
+                return Err(reader.error("Invalid NBT path element"));
+            }
+            Ok(NbtPathNode::MatchRoot(read_compound(reader)?))
+        }
+        Some('[') => {
+            reader.skip();
+            let node = match reader.peek() {
+                Some('{') => NbtPathNode::MatchElement(read_compound(reader)?),
+                Some(']') => NbtPathNode::AllElements,
+                _ => NbtPathNode::Index(reader.read_int()?),
+            };
+            reader.expect(']')?;
+            Ok(node)
+        }
+        _ => read_path_key(reader),
+    }
+}
+
+pub fn read_nbt_path(reader: &mut StringReader) -> anyhow::Result<NbtPath> {
+    let start = reader.cursor();
+    let mut nodes = Vec::new();
+    while reader.can_read() && reader.peek() != Some(' ') {
+        nodes.push(read_path_node(reader, nodes.is_empty())?);
+        if let Some(c) = reader.peek() {
+            if c != ' ' && c != '[' && c != '{' {
+                reader.expect('.')?;
+            }
+        }
+    }
+    if nodes.is_empty() {
+        return Err(reader.error("Invalid NBT path element"));
+    }
+    Ok(NbtPath { original: reader.input()[start..reader.cursor()].to_string(), nodes })
+}
+
+pub fn nbt_tag_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Nbt(read_tag(reader)?)))
+}
+
+pub fn nbt_compound_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Nbt(NbtTag::Compound(read_compound(reader)?))))
+}
+
+pub fn nbt_path_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::NbtPath(read_nbt_path(reader)?)))
+}
+
+/// Suggests path segments from the NBT data the sender is about to operate on.
+pub fn nbt_path_suggestions<T, F>(data: F) -> SuggestionFunction<T>
+where
+    F: Fn(&T) -> Option<NbtTag> + Send + Sync + 'static,
+{
+    Box::new(move |context, partial| match data(context.sender()) {
+        Some(data) => NbtPath::suggest(partial, &data),
+        None => Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::nbt::{read_nbt_path, read_tag, NbtPath, NbtPathNode, NbtTag};
+    use crate::arguments::reader::StringReader;
+
+    fn tag(input: &str) -> anyhow::Result<NbtTag> {
+        read_tag(&mut StringReader::new(input))
+    }
+
+    #[test]
+    pub fn test_snbt_types() -> anyhow::Result<()> {
+        assert_eq!(tag("1b")?, NbtTag::Byte(1));
+        assert_eq!(tag("-3s")?, NbtTag::Short(-3));
+        assert_eq!(tag("7")?, NbtTag::Int(7));
+        assert_eq!(tag("7L")?, NbtTag::Long(7));
+        assert_eq!(tag("1.5f")?, NbtTag::Float(1.5));
+        assert_eq!(tag("1.5")?, NbtTag::Double(1.5));
+        assert_eq!(tag("true")?, NbtTag::Byte(1));
+        assert_eq!(tag("300b")?, NbtTag::String("300b".into()));
+        assert_eq!(tag("[I; 1, 2]")?, NbtTag::IntArray(vec![1, 2]));
+        assert!(tag("[1, 2b]").is_err());
+        assert!(tag("[B; 1, 2]").is_err());
+        assert!(tag("{:1}").is_err());
+
+        let compound = tag("{Count: 1b, tag: {display: {Name: '\"Sword\"'}}, Tags: [a, \"b c\"]}")?;
+        assert_eq!(compound.to_string(), "{Count:1b,Tags:[\"a\",\"b c\"],tag:{display:{Name:'\"Sword\"'}}}");
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_nbt_path() -> anyhow::Result<()> {
+        let path = read_nbt_path(&mut StringReader::new("Items[0].tag.display.Name"))?;
+        assert_eq!(
+            *path.nodes(),
+            vec![
+                NbtPathNode::Child("Items".into()),
+                NbtPathNode::Index(0),
+                NbtPathNode::Child("tag".into()),
+                NbtPathNode::Child("display".into()),
+                NbtPathNode::Child("Name".into()),
+            ]
+        );
+
+        let data = tag("{Items: [{Count: 1b, id: stone}, {Count: 2b, id: dirt, tag: {Damage: 3}}]}")?;
+        let filtered = read_nbt_path(&mut StringReader::new("Items[{Count:2b}].id"))?;
+        assert_eq!(filtered.get(&data), vec![NbtTag::String("dirt".into())]);
+        let last = read_nbt_path(&mut StringReader::new("Items[-1].tag.Damage"))?;
+        assert_eq!(last.get(&data), vec![NbtTag::Int(3)]);
+        assert!(read_nbt_path(&mut StringReader::new("Items.{Count:1b}")).is_err());
+
+        assert_eq!(NbtPath::suggest("It", &data), vec!["Items"]);
+        assert_eq!(NbtPath::suggest("Items[1].", &data), vec!["Items[1].Count", "Items[1].id", "Items[1].tag"]);
+        Ok(())
+    }
+}
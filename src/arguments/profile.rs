@@ -0,0 +1,149 @@
+use crate::arguments::entity::{read_selector, EntitySelector};
+use crate::arguments::reader::StringReader;
+use crate::arguments::reader_parser;
+use crate::executor::command::ParserFunction;
+use crate::executor::context::Value;
+
+/// Parses a hyphenated UUID, accepting shortened groups the same way vanilla does.
+pub fn parse_uuid(input: &str) -> Option<u128> {
+    let groups = input.split('-').collect::<Vec<&str>>();
+    let widths = [32, 16, 16, 16, 48];
+    if groups.len() != widths.len() {
+        return None;
+    }
+    let mut uuid = 0u128;
+    for (group, width) in groups.into_iter().zip(widths.iter()) {
+        if group.is_empty() || group.len() * 4 > *width || !group.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        uuid = (uuid << width) | u128::from_str_radix(group, 16).ok()?;
+    }
+    Some(uuid)
+}
+
+/// Formats a UUID in its canonical hyphenated form.
+pub fn format_uuid(uuid: u128) -> String {
+    let hex = format!("{:032x}", uuid);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+pub fn read_uuid(reader: &mut StringReader) -> anyhow::Result<u128> {
+    let start = reader.cursor();
+    let input = reader.read_while(|c| c.is_ascii_hexdigit() || c == '-');
+    parse_uuid(input).ok_or_else(|| {
+        reader.set_cursor(start);
+        reader.error("Invalid UUID")
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameProfile {
+    pub name: String,
+    pub uuid: u128,
+}
+
+/// Looks up the profiles of online or known players for the host server.
+pub trait ProfileLookup<T> {
+    fn by_name(&self, name: &str) -> Option<GameProfile>;
+
+    fn by_uuid(&self, uuid: u128) -> Option<GameProfile>;
+
+    /// Profiles of the players a selector matches for the sender.
+    fn by_selector(&self, sender: &T, selector: &EntitySelector) -> anyhow::Result<Vec<GameProfile>>;
+}
+
+/// Parsed `minecraft:game_profile` argument, resolved to profiles at execution time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileTarget {
+    Name(String),
+    Uuid(u128),
+    Selector(Box<EntitySelector>),
+}
+
+impl ProfileTarget {
+    pub fn resolve<T, L: ProfileLookup<T>>(&self, lookup: &L, sender: &T) -> anyhow::Result<Vec<GameProfile>> {
+        match self {
+            ProfileTarget::Name(name) => Ok(vec![lookup.by_name(name).ok_or_else(|| anyhow::anyhow!("That player does not exist"))?]),
+            ProfileTarget::Uuid(uuid) => Ok(vec![lookup.by_uuid(*uuid).ok_or_else(|| anyhow::anyhow!("That player does not exist"))?]),
+            ProfileTarget::Selector(selector) => {
+                let profiles = lookup.by_selector(sender, selector)?;
+                if profiles.is_empty() {
+                    anyhow::bail!("No player was found");
+                }
+                Ok(profiles)
+            }
+        }
+    }
+}
+
+/// Reads a player name, UUID or player-only selector.
+pub fn read_game_profile(reader: &mut StringReader) -> anyhow::Result<ProfileTarget> {
+    let start = reader.cursor();
+    if reader.peek() == Some('@') {
+        let selector = read_selector(reader)?;
+        if selector.includes_entities() {
+            reader.set_cursor(start);
+            return Err(reader.error("Only players may be affected by this command, but the provided selector includes entities"));
+        }
+        return Ok(ProfileTarget::Selector(Box::new(selector)));
+    }
+    let name = reader.read_while(|c| c != ' ');
+    if name.is_empty() {
+        return Err(reader.error("Expected player name or UUID"));
+    }
+    Ok(match parse_uuid(name) {
+        Some(uuid) => ProfileTarget::Uuid(uuid),
+        None => ProfileTarget::Name(name.to_string()),
+    })
+}
+
+pub fn uuid_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Uuid(read_uuid(reader)?)))
+}
+
+pub fn game_profile_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::GameProfile(read_game_profile(reader)?)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::entity::EntitySelector;
+    use crate::arguments::profile::{format_uuid, read_game_profile, read_uuid, GameProfile, ProfileLookup, ProfileTarget};
+    use crate::arguments::reader::StringReader;
+
+    struct Players;
+
+    impl ProfileLookup<()> for Players {
+        fn by_name(&self, name: &str) -> Option<GameProfile> {
+            (name == "Notch").then(|| GameProfile { name: name.into(), uuid: 1 })
+        }
+
+        fn by_uuid(&self, uuid: u128) -> Option<GameProfile> {
+            (uuid == 1).then(|| GameProfile { name: "Notch".into(), uuid })
+        }
+
+        fn by_selector(&self, _: &(), _: &EntitySelector) -> anyhow::Result<Vec<GameProfile>> {
+            Ok(vec![GameProfile { name: "Notch".into(), uuid: 1 }, GameProfile { name: "jeb_".into(), uuid: 2 }])
+        }
+    }
+
+    #[test]
+    pub fn test_uuid() -> anyhow::Result<()> {
+        let uuid = read_uuid(&mut StringReader::new("f81d4fae-7dec-11d0-a765-00a0c91e6bf6"))?;
+        assert_eq!(format_uuid(uuid), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6");
+        assert_eq!(format_uuid(read_uuid(&mut StringReader::new("0-0-0-0-1"))?), "00000000-0000-0000-0000-000000000001");
+        assert!(read_uuid(&mut StringReader::new("f81d4fae7dec11d0a76500a0c91e6bf6")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_game_profile() -> anyhow::Result<()> {
+        let name = read_game_profile(&mut StringReader::new("Notch"))?;
+        assert_eq!(name.resolve(&Players, &())?[0].uuid, 1);
+        assert_eq!(read_game_profile(&mut StringReader::new("0-0-0-0-1"))?, ProfileTarget::Uuid(1));
+        assert_eq!(read_game_profile(&mut StringReader::new("@a"))?.resolve(&Players, &())?.len(), 2);
+        assert!(read_game_profile(&mut StringReader::new("@e")).is_err());
+        assert!(read_game_profile(&mut StringReader::new("Herobrine"))?.resolve(&Players, &()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::arguments::reader::StringReader;
+use crate::arguments::reader_parser;
+use crate::executor::command::ParserFunction;
+use crate::executor::context::Value;
+use std::str::FromStr;
+
+/// Optionally bounded range such as `..5`, `3..`, `1..10` or an exact `7`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinMaxBounds<V> {
+    min: Option<V>,
+    max: Option<V>,
+}
+
+impl<V: Copy + PartialOrd + FromStr> MinMaxBounds<V> {
+    pub fn new(min: Option<V>, max: Option<V>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn exactly(value: V) -> Self {
+        Self { min: Some(value), max: Some(value) }
+    }
+
+    pub fn min(&self) -> Option<V> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<V> {
+        self.max
+    }
+
+    pub fn contains(&self, value: V) -> bool {
+        self.min.is_none_or(|min| min <= value) && self.max.is_none_or(|max| value <= max)
+    }
+
+    fn read_bound(reader: &mut StringReader) -> anyhow::Result<Option<V>> {
+        let start = reader.cursor();
+        while let Some(c) = reader.peek() {
+            let range_separator = c == '.' && reader.peek_at(1) == Some('.');
+            if range_separator || !(c.is_ascii_digit() || c == '-' || c == '.') {
+                break;
+            }
+            reader.skip();
+        }
+        let bound = &reader.input()[start..reader.cursor()];
+        if bound.is_empty() {
+            return Ok(None);
+        }
+        match bound.parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => {
+                reader.set_cursor(start);
+                Err(reader.error(format!("Invalid number '{}'", bound)))
+            }
+        }
+    }
+
+    /// Reads a range, rejecting ranges whose min is bigger than their max.
+    pub fn read(reader: &mut StringReader) -> anyhow::Result<Self> {
+        let start = reader.cursor();
+        let bounds = Self::read_wrapped(reader)?;
+        if let (Some(min), Some(max)) = (bounds.min, bounds.max) {
+            if min > max {
+                reader.set_cursor(start);
+                return Err(reader.error("Min cannot be bigger than max"));
+            }
+        }
+        Ok(bounds)
+    }
+
+    /// Reads a range which may wrap around, such as a rotation range of `170..-170`.
+    pub fn read_wrapped(reader: &mut StringReader) -> anyhow::Result<Self> {
+        let start = reader.cursor();
+        let min = Self::read_bound(reader)?;
+        let max = if reader.peek() == Some('.') && reader.peek_at(1) == Some('.') {
+            reader.skip();
+            reader.skip();
+            Self::read_bound(reader)?
+        } else {
+            min
+        };
+        if min.is_none() && max.is_none() {
+            reader.set_cursor(start);
+            return Err(reader.error("Expected value or range of values"));
+        }
+        Ok(Self { min, max })
+    }
+}
+
+pub fn int_range_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::IntRange(MinMaxBounds::read(reader)?)))
+}
+
+pub fn float_range_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::FloatRange(MinMaxBounds::read(reader)?)))
+}
+
+/// Parser for `minecraft:range`, reading float bounds only when `decimals` is set.
+pub fn range_parser<T>(decimals: bool) -> ParserFunction<T> {
+    if decimals {
+        float_range_parser()
+    } else {
+        int_range_parser()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::range::MinMaxBounds;
+    use crate::arguments::reader::StringReader;
+
+    fn int_range(input: &str) -> anyhow::Result<MinMaxBounds<i32>> {
+        MinMaxBounds::read(&mut StringReader::new(input))
+    }
+
+    #[test]
+    pub fn test_ranges() -> anyhow::Result<()> {
+        assert_eq!(int_range("..5")?, MinMaxBounds::new(None, Some(5)));
+        assert_eq!(int_range("3..")?, MinMaxBounds::new(Some(3), None));
+        assert_eq!(int_range("7")?, MinMaxBounds::exactly(7));
+        assert!(int_range("1..10")?.contains(10) && !int_range("1..10")?.contains(11));
+        assert!(int_range("10..1").is_err());
+        assert!(int_range("1.5..2").is_err());
+        assert!(int_range("..").is_err());
+
+        let floats = MinMaxBounds::<f64>::read(&mut StringReader::new("1.5..2"))?;
+        assert!(floats.contains(1.75) && !floats.contains(1.0));
+        assert_eq!(MinMaxBounds::<f64>::read_wrapped(&mut StringReader::new("170..-170"))?.min(), Some(170.0));
+        Ok(())
+    }
+}
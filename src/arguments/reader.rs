@@ -0,0 +1,192 @@
+/// Cursor over command input, modelled after Brigadier's `StringReader`.
+#[derive(Debug, Clone)]
+pub struct StringReader<'a> {
+    input: &'a str,
+    cursor: usize,
+}
+
+pub fn is_allowed_in_unquoted_string(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+pub fn is_allowed_number(c: char) -> bool {
+    c.is_ascii_digit() || c == '.' || c == '-'
+}
+
+pub fn is_allowed_in_resource_location(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-' || c == '.' || c == ':' || c == '/'
+}
+
+impl<'a> StringReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, cursor: 0 }
+    }
+
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor;
+    }
+
+    pub fn read_so_far(&self) -> &'a str {
+        &self.input[..self.cursor]
+    }
+
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.cursor..]
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.cursor < self.input.len()
+    }
+
+    pub fn can_read_length(&self, length: usize) -> bool {
+        self.remaining().chars().count() >= length
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    pub fn peek_at(&self, offset: usize) -> Option<char> {
+        self.remaining().chars().nth(offset)
+    }
+
+    pub fn read(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.cursor += c.len_utf8();
+        Some(c)
+    }
+
+    pub fn skip(&mut self) {
+        self.read();
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.skip();
+        }
+    }
+
+    /// Builds an error pointing at the current cursor, in the same shape as Brigadier's syntax
+    /// errors.
+    pub fn error<S: AsRef<str>>(&self, message: S) -> anyhow::Error {
+        anyhow::anyhow!("{} at position {}: {}<--[HERE]", message.as_ref(), self.cursor, self.read_so_far())
+    }
+
+    pub fn expect(&mut self, c: char) -> anyhow::Result<()> {
+        if self.peek() != Some(c) {
+            return Err(self.error(format!("Expected '{}'", c)));
+        }
+        self.skip();
+        Ok(())
+    }
+
+    pub fn read_while<P: Fn(char) -> bool>(&mut self, predicate: P) -> &'a str {
+        let start = self.cursor;
+        while self.peek().is_some_and(&predicate) {
+            self.skip();
+        }
+        &self.input[start..self.cursor]
+    }
+
+    pub fn read_unquoted_string(&mut self) -> &'a str {
+        self.read_while(is_allowed_in_unquoted_string)
+    }
+
+    pub fn read_quoted_string(&mut self) -> anyhow::Result<String> {
+        let quote = match self.peek() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error("Expected quote to start a string")),
+        };
+        self.skip();
+        self.read_string_until(quote)
+    }
+
+    pub fn read_string_until(&mut self, terminator: char) -> anyhow::Result<String> {
+        let mut result = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.read() {
+            if escaped {
+                if c != terminator && c != '\\' {
+                    self.cursor -= c.len_utf8();
+                    return Err(self.error(format!("Invalid escape sequence '{}' in quoted string", c)));
+                }
+                result.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == terminator {
+                return Ok(result);
+            } else {
+                result.push(c);
+            }
+        }
+        Err(self.error("Unclosed quoted string"))
+    }
+
+    pub fn read_string(&mut self) -> anyhow::Result<String> {
+        match self.peek() {
+            Some('"' | '\'') => self.read_quoted_string(),
+            _ => Ok(self.read_unquoted_string().to_string()),
+        }
+    }
+
+    fn read_number<V: std::str::FromStr>(&mut self, kind: &str) -> anyhow::Result<V> {
+        let start = self.cursor;
+        let number = self.read_while(is_allowed_number);
+        if number.is_empty() {
+            return Err(self.error(format!("Expected {}", kind)));
+        }
+        match number.parse() {
+            Ok(value) => Ok(value),
+            Err(_) => {
+                self.cursor = start;
+                Err(self.error(format!("Invalid {} '{}'", kind, number)))
+            }
+        }
+    }
+
+    pub fn read_int(&mut self) -> anyhow::Result<i32> {
+        self.read_number("integer")
+    }
+
+    pub fn read_long(&mut self) -> anyhow::Result<i64> {
+        self.read_number("long")
+    }
+
+    pub fn read_float(&mut self) -> anyhow::Result<f32> {
+        self.read_number("float")
+    }
+
+    pub fn read_double(&mut self) -> anyhow::Result<f64> {
+        self.read_number("double")
+    }
+
+    pub fn read_boolean(&mut self) -> anyhow::Result<bool> {
+        let start = self.cursor;
+        match self.read_string()?.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            "" => Err(self.error("Expected bool")),
+            other => {
+                self.cursor = start;
+                Err(self.error(format!("Invalid bool, expected true or false but found '{}'", other)))
+            }
+        }
+    }
+
+    pub fn read_resource_location(&mut self) -> anyhow::Result<&'a str> {
+        let location = self.read_while(is_allowed_in_resource_location);
+        if location.is_empty() {
+            return Err(self.error("Expected resource location"));
+        }
+        Ok(location)
+    }
+}
@@ -0,0 +1,165 @@
+use crate::arguments::misc::Color;
+use crate::arguments::reader::StringReader;
+use crate::arguments::{fixed_suggestions, reader_parser};
+use crate::executor::command::{ParserFunction, SuggestionFunction};
+use crate::executor::context::Value;
+
+/// Division rounding towards negative infinity, like Java's `Math.floorDiv`.
+fn floor_div(dividend: i32, divisor: i32) -> i32 {
+    let quotient = dividend.wrapping_div(divisor);
+    if dividend.wrapping_rem(divisor) != 0 && (dividend < 0) != (divisor < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+/// Parsed `minecraft:operation` argument, as used by `/scoreboard players operation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Assign,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Min,
+    Max,
+    Swap,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 9] = [
+        Operation::Assign,
+        Operation::Add,
+        Operation::Subtract,
+        Operation::Multiply,
+        Operation::Divide,
+        Operation::Modulo,
+        Operation::Min,
+        Operation::Max,
+        Operation::Swap,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operation::Assign => "=",
+            Operation::Add => "+=",
+            Operation::Subtract => "-=",
+            Operation::Multiply => "*=",
+            Operation::Divide => "/=",
+            Operation::Modulo => "%=",
+            Operation::Min => "<",
+            Operation::Max => ">",
+            Operation::Swap => "><",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Operation> {
+        Operation::ALL.iter().copied().find(|operation| operation.symbol() == symbol)
+    }
+
+    /// Applies the operation to the target and source scores, returning both new values.
+    pub fn apply(&self, target: i32, source: i32) -> anyhow::Result<(i32, i32)> {
+        let divisor = || if source == 0 { Err(anyhow::anyhow!("Can't divide by zero")) } else { Ok(source) };
+        Ok(match self {
+            Operation::Assign => (source, source),
+            Operation::Add => (target.wrapping_add(source), source),
+            Operation::Subtract => (target.wrapping_sub(source), source),
+            Operation::Multiply => (target.wrapping_mul(source), source),
+            Operation::Divide => (floor_div(target, divisor()?), source),
+            Operation::Modulo => (target.wrapping_sub(floor_div(target, divisor()?).wrapping_mul(source)), source),
+            Operation::Min => (target.min(source), source),
+            Operation::Max => (target.max(source), source),
+            Operation::Swap => (source, target),
+        })
+    }
+}
+
+/// Parsed `minecraft:scoreboard_slot` argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreboardSlot {
+    List,
+    Sidebar,
+    BelowName,
+    TeamSidebar(Color),
+}
+
+impl ScoreboardSlot {
+    pub fn name(&self) -> String {
+        match self {
+            ScoreboardSlot::List => "list".into(),
+            ScoreboardSlot::Sidebar => "sidebar".into(),
+            ScoreboardSlot::BelowName => "belowName".into(),
+            ScoreboardSlot::TeamSidebar(color) => format!("sidebar.team.{}", color.name()),
+        }
+    }
+
+    pub fn all() -> Vec<ScoreboardSlot> {
+        let mut slots = vec![ScoreboardSlot::List, ScoreboardSlot::Sidebar, ScoreboardSlot::BelowName];
+        slots.extend(Color::ALL.iter().filter(|color| **color != Color::Reset).map(|color| ScoreboardSlot::TeamSidebar(*color)));
+        slots
+    }
+
+    pub fn from_name(name: &str) -> Option<ScoreboardSlot> {
+        ScoreboardSlot::all().into_iter().find(|slot| slot.name() == name)
+    }
+}
+
+pub fn read_operation(reader: &mut StringReader) -> anyhow::Result<Operation> {
+    let start = reader.cursor();
+    let symbol = reader.read_while(|c| c != ' ');
+    Operation::from_symbol(symbol).ok_or_else(|| {
+        reader.set_cursor(start);
+        reader.error("Invalid operation")
+    })
+}
+
+pub fn read_scoreboard_slot(reader: &mut StringReader) -> anyhow::Result<ScoreboardSlot> {
+    let start = reader.cursor();
+    let name = reader.read_unquoted_string();
+    ScoreboardSlot::from_name(name).ok_or_else(|| {
+        reader.set_cursor(start);
+        reader.error(format!("Unknown display slot '{}'", name))
+    })
+}
+
+pub fn operation_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::Operation(read_operation(reader)?)))
+}
+
+pub fn scoreboard_slot_parser<T>() -> ParserFunction<T> {
+    reader_parser(|reader| Ok(Value::ScoreboardSlot(read_scoreboard_slot(reader)?)))
+}
+
+pub fn operation_suggestions<T>() -> SuggestionFunction<T> {
+    fixed_suggestions(Operation::ALL.iter().map(|operation| operation.symbol().to_string()).collect())
+}
+
+pub fn scoreboard_slot_suggestions<T>() -> SuggestionFunction<T> {
+    fixed_suggestions(ScoreboardSlot::all().iter().map(ScoreboardSlot::name).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::misc::Color;
+    use crate::arguments::reader::StringReader;
+    use crate::arguments::scoreboard::{read_operation, read_scoreboard_slot, Operation, ScoreboardSlot};
+
+    #[test]
+    pub fn test_operations() -> anyhow::Result<()> {
+        assert_eq!(read_operation(&mut StringReader::new("+= 1"))?, Operation::Add);
+        assert_eq!(read_operation(&mut StringReader::new("><"))?, Operation::Swap);
+        assert!(read_operation(&mut StringReader::new("==")).is_err());
+        assert!(read_operation(&mut StringReader::new("^=")).is_err());
+
+        assert_eq!(Operation::Divide.apply(-7, 2)?, (-4, 2));
+        assert_eq!(Operation::Modulo.apply(-7, 2)?, (1, 2));
+        assert_eq!(Operation::Swap.apply(1, 2)?, (2, 1));
+        assert!(Operation::Divide.apply(1, 0).is_err());
+
+        assert_eq!(read_scoreboard_slot(&mut StringReader::new("sidebar.team.red"))?, ScoreboardSlot::TeamSidebar(Color::Red));
+        assert!(read_scoreboard_slot(&mut StringReader::new("sidebar.team.reset")).is_err());
+        Ok(())
+    }
+}
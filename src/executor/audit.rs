@@ -0,0 +1,338 @@
+use crate::arguments::block::ResourceTarget;
+use crate::arguments::coordinates::{Axis, Coordinates, WorldCoordinate};
+use crate::arguments::entity::{EntitySelector, ScoreHolder, Target, TargetVariable};
+use crate::arguments::nbt::NbtTag;
+use crate::arguments::profile::{format_uuid, ProfileTarget};
+use crate::arguments::range::MinMaxBounds;
+use crate::executor::context::{CommandContext, Value};
+use crate::executor::middleware::{Invocation, Middleware, SenderIdentity};
+use std::fmt::{Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Succeeded,
+    Failed(String),
+    /// Middleware refused to run the command, e.g. because of a cooldown.
+    Vetoed(String),
+}
+
+/// Record of a single command execution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    pub timestamp: SystemTime,
+    pub sender: String,
+    pub input: String,
+    pub path: Vec<String>,
+    /// Identifiers of the parsed arguments with their values, see `value_to_json`.
+    pub arguments: Vec<(String, serde_json::Value)>,
+    pub outcome: AuditOutcome,
+    pub duration: Duration,
+}
+
+impl AuditEvent {
+    /// The event as a single line JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
+        let (outcome, error) = match &self.outcome {
+            AuditOutcome::Succeeded => ("succeeded", None),
+            AuditOutcome::Failed(error) => ("failed", Some(error)),
+            AuditOutcome::Vetoed(error) => ("vetoed", Some(error)),
+        };
+        let arguments = self.arguments.iter().cloned().collect::<serde_json::Map<String, serde_json::Value>>();
+        serde_json::json!({
+            "timestamp": self.timestamp.duration_since(UNIX_EPOCH).map(|since| since.as_millis() as u64).unwrap_or_default(),
+            "sender": self.sender,
+            "input": self.input,
+            "path": self.path,
+            "arguments": arguments,
+            "outcome": outcome,
+            "error": error,
+            "duration_us": self.duration.as_micros() as u64,
+        })
+    }
+}
+
+impl Display for AuditEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ran /{} in {:?}", self.sender, self.input, self.duration)?;
+        match &self.outcome {
+            AuditOutcome::Succeeded => Ok(()),
+            AuditOutcome::Failed(error) => write!(f, ", failed: {}", error),
+            AuditOutcome::Vetoed(error) => write!(f, ", refused: {}", error),
+        }
+    }
+}
+
+fn coordinate_json(coordinate: WorldCoordinate) -> serde_json::Value {
+    serde_json::json!({ "value": coordinate.value(), "relative": coordinate.is_relative() })
+}
+
+fn bounds_json<V: Copy + PartialOrd + std::str::FromStr + Into<serde_json::Value>>(bounds: &MinMaxBounds<V>) -> serde_json::Value {
+    serde_json::json!({ "min": bounds.min().map(Into::into), "max": bounds.max().map(Into::into) })
+}
+
+fn resource_json(target: &ResourceTarget) -> serde_json::Value {
+    let prefix = if target.is_tag() { "#" } else { "" };
+    format!("{}{}", prefix, target.location()).into()
+}
+
+fn selector_json(selector: &EntitySelector) -> serde_json::Value {
+    match selector.target() {
+        Target::Player(name) => name.as_str().into(),
+        Target::Uuid(uuid) => format_uuid(*uuid).into(),
+        Target::Variable(variable) => match variable {
+            TargetVariable::NearestPlayer => "@p",
+            TargetVariable::RandomPlayer => "@r",
+            TargetVariable::AllPlayers => "@a",
+            TargetVariable::AllEntities => "@e",
+            TargetVariable::Executor => "@s",
+        }
+        .into(),
+    }
+}
+
+/// Converts a parsed value to JSON: numbers as numbers, names and identifiers as strings, NBT as
+/// SNBT, and coordinates, rotations and ranges as objects.
+///
+/// Selectors are reduced to their target variable, name or UUID; their options are part of the
+/// input of the event.
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(value) | Value::Generic(value) => value.as_str().into(),
+        Value::Integer(value) | Value::Time(value) => (*value).into(),
+        Value::Float(value) => (*value).into(),
+        Value::ItemSlot(value) => (*value).into(),
+        Value::Entity(selector) => selector_json(selector),
+        Value::ScoreHolder(ScoreHolder::Wildcard) => "*".into(),
+        Value::ScoreHolder(ScoreHolder::Name(name)) => name.as_str().into(),
+        Value::ScoreHolder(ScoreHolder::Selector(selector)) => selector_json(selector),
+        Value::Coordinates(Coordinates::World { x, y, z }) => {
+            serde_json::json!({ "x": coordinate_json(*x), "y": coordinate_json(*y), "z": coordinate_json(*z) })
+        }
+        Value::Coordinates(Coordinates::Local { left, up, forwards }) => {
+            serde_json::json!({ "left": left, "up": up, "forwards": forwards })
+        }
+        Value::Rotation(rotation) => serde_json::json!({ "yaw": coordinate_json(rotation.yaw()), "pitch": coordinate_json(rotation.pitch()) }),
+        Value::Angle(angle) => coordinate_json(angle.coordinate()),
+        Value::IntRange(bounds) => bounds_json(bounds),
+        Value::FloatRange(bounds) => bounds_json(bounds),
+        Value::Nbt(tag) => tag.to_string().into(),
+        Value::NbtPath(path) => path.original().into(),
+        Value::Block(block) => serde_json::json!({
+            "block": resource_json(block.target()),
+            "properties": block.properties(),
+            "nbt": block.nbt().map(|nbt| NbtTag::Compound(nbt.clone()).to_string()),
+        }),
+        Value::Item(item) => serde_json::json!({ "item": resource_json(item.target()), "nbt": item.nbt().map(|nbt| NbtTag::Compound(nbt.clone()).to_string()) }),
+        Value::Component(component) => component.plain_text().into(),
+        Value::Message(message) => message.text().into(),
+        Value::Swizzle(swizzle) => [(Axis::X, 'x'), (Axis::Y, 'y'), (Axis::Z, 'z')]
+            .into_iter()
+            .filter(|(axis, _)| swizzle.contains(*axis))
+            .map(|(_, name)| name)
+            .collect::<String>()
+            .into(),
+        Value::Operation(operation) => operation.symbol().into(),
+        Value::ScoreboardSlot(slot) => slot.name().into(),
+        Value::EntityAnchor(anchor) => anchor.name().into(),
+        Value::Color(color) => color.name().into(),
+        Value::Identifier(location) => location.to_string().into(),
+        Value::Function(target) => resource_json(target),
+        Value::Uuid(uuid) => format_uuid(*uuid).into(),
+        Value::GameProfile(ProfileTarget::Name(name)) => name.as_str().into(),
+        Value::GameProfile(ProfileTarget::Uuid(uuid)) => format_uuid(*uuid).into(),
+        Value::GameProfile(ProfileTarget::Selector(selector)) => selector_json(selector),
+    }
+}
+
+/// Destination of audit events.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &AuditEvent);
+}
+
+impl<F: Fn(&AuditEvent) + Send + Sync> AuditSink for F {
+    fn record(&self, event: &AuditEvent) {
+        self(event)
+    }
+}
+
+/// Writes events through the `log` crate under the `commander::audit` target; failures are
+/// logged as warnings.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogSink;
+
+impl AuditSink for LogSink {
+    fn record(&self, event: &AuditEvent) {
+        match event.outcome {
+            AuditOutcome::Succeeded => log::info!(target: "commander::audit", "{}", event),
+            _ => log::warn!(target: "commander::audit", "{}", event),
+        }
+    }
+}
+
+/// Appends every event as a line of JSON.
+pub struct JsonLinesSink<W: Write + Send> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+
+    pub fn into_inner(self) -> anyhow::Result<W> {
+        self.writer.into_inner().map_err(|_| anyhow::anyhow!("Audit writer lock was poisoned."))
+    }
+}
+
+impl JsonLinesSink<BufWriter<File>> {
+    /// Appends to the file at the path, creating it if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Ok(Self::new(BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?)))
+    }
+}
+
+impl<W: Write + Send> AuditSink for JsonLinesSink<W> {
+    fn record(&self, event: &AuditEvent) {
+        let written = match self.writer.lock() {
+            Ok(mut writer) => writeln!(writer, "{}", event.to_json()).and_then(|_| writer.flush()).map_err(anyhow::Error::from),
+            Err(_) => Err(anyhow::anyhow!("Audit writer lock was poisoned.")),
+        };
+        if let Err(error) = written {
+            log::error!(target: "commander::audit", "Failed to write audit event: {}", error);
+        }
+    }
+}
+
+struct Pending {
+    timestamp: SystemTime,
+    sender: String,
+    arguments: Vec<(String, serde_json::Value)>,
+}
+
+/// Middleware sending an `AuditEvent` for every command which reached its command function to
+/// the sinks, including executions refused by other middleware added after it.
+pub struct AuditLog {
+    sinks: Vec<Box<dyn AuditSink>>,
+}
+
+impl AuditLog {
+    pub fn new<S: AuditSink + 'static>(sink: S) -> Self {
+        Self { sinks: vec![Box::new(sink)] }
+    }
+
+    pub fn with_sink<S: AuditSink + 'static>(mut self, sink: S) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+}
+
+impl<T: SenderIdentity> Middleware<T> for AuditLog {
+    fn before(&self, invocation: &mut Invocation, context: &CommandContext<T>) -> anyhow::Result<()> {
+        let arguments = context
+            .value_args()
+            .borrow_inner()
+            .iter()
+            .map(|(identifier, value)| (identifier.to_string(), value_to_json(value)))
+            .collect();
+        invocation.state_mut().insert(Pending { timestamp: SystemTime::now(), sender: context.sender().identity(), arguments });
+        Ok(())
+    }
+
+    fn after(&self, invocation: &Invocation, result: anyhow::Result<()>) -> anyhow::Result<()> {
+        if let Some(pending) = invocation.state().get::<Pending>() {
+            let outcome = match (&result, invocation.is_executed()) {
+                (Ok(()), _) => AuditOutcome::Succeeded,
+                (Err(error), true) => AuditOutcome::Failed(error.to_string()),
+                (Err(error), false) => AuditOutcome::Vetoed(error.to_string()),
+            };
+            let event = AuditEvent {
+                timestamp: pending.timestamp,
+                sender: pending.sender.clone(),
+                input: invocation.input().to_string(),
+                path: invocation.path().iter().map(|segment| segment.to_string()).collect(),
+                arguments: pending.arguments.clone(),
+                outcome,
+                duration: invocation.elapsed(),
+            };
+            for sink in &self.sinks {
+                sink.record(&event);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::coordinates::{Coordinates, WorldCoordinate};
+    use crate::arguments::range::MinMaxBounds;
+    use crate::executor::audit::{value_to_json, AuditEvent, AuditLog, AuditOutcome, AuditSink, JsonLinesSink};
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand};
+    use crate::executor::context::{CommandContext, Value};
+    use crate::executor::cooldown::Cooldowns;
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::protocol::{Parser, StringDescription};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    pub fn test_audit_log() -> anyhow::Result<()> {
+        let events = Arc::new(Mutex::new(Vec::<AuditEvent>::new()));
+        let recorded = events.clone();
+        let lines = Arc::new(JsonLinesSink::new(Vec::new()));
+        let json = lines.clone();
+        let audit = AuditLog::new(move |event: &AuditEvent| recorded.lock().unwrap().push(event.clone()))
+            .with_sink(move |event: &AuditEvent| json.record(event));
+
+        let mut kick = Command::Natural(NaturalCommand::default());
+        kick.child(
+            "player",
+            Command::ArgParser(ArgParserCommand::executable(
+                Box::new(|context: CommandContext<String>| match context.value("player") {
+                    Some(Value::String(player)) if player == "Admin" => anyhow::bail!("Cannot kick an operator"),
+                    _ => Ok(()),
+                }),
+                "player".into(),
+                Parser::String { info: StringDescription::SingleWord },
+                Box::new(|mut context| {
+                    let player = context.args()[0].to_string();
+                    context.trim_top();
+                    Ok((Value::String(player), context))
+                }),
+            )),
+        )?;
+        let mut executor = Executor::new()
+            .with_middleware(audit)
+            .with_middleware(Cooldowns::new().cooldown("kick", Duration::from_secs(60)));
+        executor.child("kick", kick)?;
+
+        executor.execute_context(CommandContext::from_input("Moderator".into(), "kick Griefer"))?.unwrap()?;
+        assert!(executor.execute_context(CommandContext::from_input("Moderator".into(), "kick Admin"))?.unwrap().is_err());
+        assert!(executor.execute_context(CommandContext::from_input("Owner".into(), "kick Admin"))?.unwrap().is_err());
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!((events[0].sender.as_str(), events[0].input.as_str()), ("Moderator", "kick Griefer"));
+        assert_eq!(events[0].path, vec!["kick", "player"]);
+        assert_eq!(events[0].arguments, vec![("player".to_string(), serde_json::Value::from("Griefer"))]);
+        assert!(matches!(events[1].outcome, AuditOutcome::Vetoed(_)));
+        assert_eq!(events[2].outcome, AuditOutcome::Failed("Cannot kick an operator".into()));
+
+        drop(executor);
+        let written = String::from_utf8(Arc::try_unwrap(lines).ok().unwrap().into_inner()?)?;
+        let first = serde_json::from_str::<serde_json::Value>(written.lines().next().unwrap())?;
+        assert_eq!(first["outcome"], "succeeded");
+        assert_eq!(first["arguments"]["player"], "Griefer");
+
+        let position = Value::Coordinates(Coordinates::World { x: WorldCoordinate::new(true, 1.5), y: WorldCoordinate::new(false, 64.0), z: WorldCoordinate::new(true, 0.0) });
+        assert_eq!(value_to_json(&Value::Integer(3)), serde_json::json!(3));
+        assert_eq!(value_to_json(&position)["x"], serde_json::json!({ "value": 1.5, "relative": true }));
+        assert_eq!(value_to_json(&Value::IntRange(MinMaxBounds::new(None, Some(5)))), serde_json::json!({ "min": null, "max": 5 }));
+        Ok(())
+    }
+}
@@ -1,10 +1,141 @@
 use super::context::{CommandContext, Value};
 use std::collections::HashMap;
 use std::borrow::{BorrowMut, Borrow};
-use crate::executor::CommandChildContainer;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::executor::function::ChainLimitError;
+use crate::executor::matching::LiteralMatching;
+use crate::executor::middleware::{Invocation, Middleware};
+use crate::executor::redirect::{self, Redirect};
+use crate::executor::trace::{ExecutionTrace, TraceEvent};
+use crate::executor::unknown::Miss;
+use crate::protocol::{Parser, SuggestionsType};
 
 pub type CommandFunction<T> = Box<dyn Fn(CommandContext<T>) -> anyhow::Result<()> + Send + Sync>;
 pub type ParserFunction<T> = Box<dyn Fn(CommandContext<T>) -> anyhow::Result<(Value, CommandContext<T>)> + Send + Sync>;
+/// Suggests completions for a partial argument, given as the remaining input joined by spaces.
+pub type SuggestionFunction<T> = Box<dyn Fn(&CommandContext<T>, &str) -> Vec<String> + Send + Sync>;
+/// Decides whether a sender may use a node; nodes a sender can't use behave as if they didn't exist.
+pub type Requirement<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// State shared by a single walk through the command tree.
+pub struct Dispatch<'a, T> {
+    matching: &'a LiteralMatching,
+    trace: Option<&'a mut ExecutionTrace>,
+    root: Option<&'a Next<T>>,
+    middleware: &'a [Arc<dyn Middleware<T>>],
+    path: Vec<&'a str>,
+    successes: usize,
+    attempts: usize,
+    limit: Option<usize>,
+    limited: bool,
+    miss: Option<Miss>,
+}
+
+impl<'a, T> Dispatch<'a, T> {
+    pub fn new(matching: &'a LiteralMatching) -> Self {
+        Self { matching, trace: None, root: None, middleware: &[], path: Vec::new(), successes: 0, attempts: 0, limit: None, limited: false, miss: None }
+    }
+
+    pub fn traced(matching: &'a LiteralMatching, trace: &'a mut ExecutionTrace) -> Self {
+        Self { matching, trace: Some(trace), root: None, middleware: &[], path: Vec::new(), successes: 0, attempts: 0, limit: None, limited: false, miss: None }
+    }
+
+    /// Sets the root redirects are resolved against.
+    pub fn with_root(mut self, root: &'a Next<T>) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Sets the middleware consulted before every command function runs.
+    pub fn with_middleware(mut self, middleware: &'a [Arc<dyn Middleware<T>>]) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
+    /// Literals and argument identifiers of the nodes matched so far, starting at the root or the
+    /// target of the last redirect.
+    ///
+    /// Only middleware reads the path, so it stays empty and never allocates without middleware.
+    pub fn path(&self) -> &[&'a str] {
+        &self.path
+    }
+
+    fn tracks_path(&self) -> bool {
+        !self.middleware.is_empty()
+    }
+
+    fn push_path(&mut self, segment: &'a str) {
+        if self.tracks_path() {
+            self.path.push(segment);
+        }
+    }
+
+    fn pop_path(&mut self) {
+        self.path.pop();
+    }
+
+    /// Continues the path at a redirect target, returning the outer path to restore afterwards.
+    pub(crate) fn enter_path(&mut self, target: &'a [String]) -> Vec<&'a str> {
+        let path = if self.tracks_path() { target.iter().map(String::as_str).collect() } else { Vec::new() };
+        std::mem::replace(&mut self.path, path)
+    }
+
+    pub(crate) fn restore_path(&mut self, outer: Vec<&'a str>) {
+        self.path = outer;
+    }
+
+    pub fn matching(&self) -> &'a LiteralMatching {
+        self.matching
+    }
+
+    /// Number of command functions which ran successfully, counting every forked sender.
+    pub fn successes(&self) -> usize {
+        self.successes
+    }
+
+    /// Stops running command functions once `limit` of them were attempted, like the
+    /// `maxCommandChainLength` game rule; further ones fail with a `ChainLimitError`.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Number of command functions which were started, whether they succeeded or not.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.limit.is_some_and(|limit| self.attempts >= limit)
+    }
+
+    /// Whether the limit kept a command function or forked branch from running.
+    pub fn is_limited(&self) -> bool {
+        self.limited
+    }
+
+    /// Marks the dispatch as limited if the limit was reached, returning whether it was.
+    pub(crate) fn refuse(&mut self) -> bool {
+        self.limited |= self.is_exhausted();
+        self.limited
+    }
+
+    pub(crate) fn miss(&self) -> Option<&Miss> {
+        self.miss.as_ref()
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Records an event if tracing is enabled; the event is only built when it will be kept.
+    pub fn record<F: FnOnce() -> TraceEvent>(&mut self, event: F) {
+        if let Some(trace) = &mut self.trace {
+            trace.push(event());
+        }
+    }
+}
 
 pub enum Either<L, R> {
     Left(L),
@@ -28,6 +159,36 @@ pub enum Command<T> {
 }
 
 impl<T> Command<T> {
+    pub fn is_executable(&self) -> bool {
+        match self {
+            Command::Natural(inner) => inner.command_function.is_some(),
+            Command::ArgParser(inner) => inner.command_function.is_some(),
+        }
+    }
+
+    pub fn next(&self) -> &Next<T> {
+        match self {
+            Command::Natural(inner) => inner.next.borrow(),
+            Command::ArgParser(inner) => inner.next.borrow(),
+        }
+    }
+
+    pub fn redirect(&self) -> Option<&Redirect<T>> {
+        match self {
+            Command::Natural(inner) => inner.redirect.as_ref(),
+            Command::ArgParser(inner) => inner.redirect.as_ref(),
+        }
+    }
+
+    /// Whether the requirement of this node, if any, accepts the sender.
+    pub fn can_use(&self, sender: &T) -> bool {
+        let requirement = match self {
+            Command::Natural(inner) => &inner.requirement,
+            Command::ArgParser(inner) => &inner.requirement,
+        };
+        requirement.as_ref().is_none_or(|requirement| requirement(sender))
+    }
+
     fn __child(next: &mut Next<T>, identifier: String, command: Command<T>) -> anyhow::Result<Option<Box<Next<T>>>> {
         match next {
             Next::ArgumentParser(_) => anyhow::bail!("Cannot assign a child to a command with a arg parser child."),
@@ -45,23 +206,59 @@ impl<T> Command<T> {
         }
     }
 
-    fn __next(next: &Next<T>, mut context: CommandContext<T>) -> anyhow::Result<Either<CommandContext<T>, Option<anyhow::Result<()>>>> {
+    pub(crate) fn __next<'a>(next: &'a Next<T>, mut context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>, root: bool) -> anyhow::Result<Either<CommandContext<T>, Option<anyhow::Result<()>>>> {
         match next {
             Next::ArgumentParser(parser_command) => {
                 match parser_command {
                     Command::Natural(_) => unreachable!(),
+                    Command::ArgParser(_) if !parser_command.can_use(context.sender()) => Ok(Either::Left(context)),
                     Command::ArgParser(parser) => {
-                        let resolved_context: CommandContext<T> = parser.parse(context)?;
-                        Ok(Either::Right(Command::execute_context(parser_command, resolved_context)?))
+                        let before = if dispatch.is_tracing() { Some(context.args().to_vec()) } else { None };
+                        let resolved_context: CommandContext<T> = match parser.parse(context) {
+                            Ok(resolved_context) => resolved_context,
+                            Err(error) => {
+                                dispatch.record(|| TraceEvent::ParserFailed {
+                                    identifier: parser.identifier.to_string(),
+                                    parser: parser.parser.identifier(),
+                                    reason: format!("{:#}", error),
+                                });
+                                return Err(error);
+                            }
+                        };
+                        if let Some(before) = before {
+                            let consumed = before.len().saturating_sub(resolved_context.args().len());
+                            dispatch.record(|| TraceEvent::Parsed {
+                                identifier: parser.identifier.to_string(),
+                                parser: parser.parser.identifier(),
+                                consumed: before[..consumed].to_vec(),
+                            });
+                        }
+                        dispatch.push_path(parser.identifier());
+                        let pushed = parser_command.execute_dispatch(resolved_context, dispatch);
+                        dispatch.pop_path();
+                        Ok(Either::Right(pushed?))
                     }
                 }
             }
             Next::LiteralMap(sub_commands) => {
                 if let Some(first_subbed) = context.args().first() {
-                    if let Some(command) = sub_commands.get(first_subbed) {
+                    let resolved = dispatch.matching().resolve(sub_commands, first_subbed, root).filter(|(_, command)| command.can_use(context.sender()));
+                    if let Some((literal, command)) = resolved {
+                        dispatch.record(|| TraceEvent::Literal { token: first_subbed.to_string(), literal: literal.clone() });
                         context.trim_top();
-                        Ok(Either::Right(Command::execute_context(command, context)?))
+                        dispatch.push_path(literal);
+                        let pushed = command.execute_dispatch(context, dispatch);
+                        dispatch.pop_path();
+                        Ok(Either::Right(pushed?))
                     } else {
+                        dispatch.record(|| TraceEvent::UnknownLiteral { token: first_subbed.to_string() });
+                        let suggestions = dispatch
+                            .matching()
+                            .similar(sub_commands, first_subbed, root)
+                            .into_iter()
+                            .filter(|literal| sub_commands[literal].can_use(context.sender()))
+                            .collect();
+                        dispatch.miss = Some(Miss { args: context.args().clone(), suggestions });
                         Ok(Either::Left(context))
                     }
                 } else {
@@ -74,6 +271,138 @@ impl<T> Command<T> {
     fn __execute(function: &Option<CommandFunction<T>>, context: CommandContext<T>) -> Option<anyhow::Result<()>> {
         function.as_ref().map(|f| f(context))
     }
+
+    /// Runs the function between the `before` and `after` hooks of the middleware.
+    fn __intercept(function: &CommandFunction<T>, middleware: &[Arc<dyn Middleware<T>>], path: &[&str], context: CommandContext<T>) -> anyhow::Result<()> {
+        let mut invocation = Invocation::new(path, context.args().input().clone());
+        let mut entered = 0;
+        let mut result = Ok(());
+        for layer in middleware {
+            entered += 1;
+            if let Err(error) = layer.before(&mut invocation, &context) {
+                result = Err(error);
+                break;
+            }
+        }
+        if result.is_ok() {
+            let started = Instant::now();
+            result = function(context);
+            invocation.finish(started.elapsed());
+        }
+        for layer in middleware[..entered].iter().rev() {
+            result = layer.after(&invocation, result);
+        }
+        result
+    }
+
+    /// Runs the command function of this command, counting it if it succeeds.
+    ///
+    /// Middleware runs around the function and can veto it or replace its result.
+    pub(crate) fn run(&self, context: CommandContext<T>, dispatch: &mut Dispatch<T>) -> Option<anyhow::Result<()>> {
+        let command_function = match self {
+            Command::Natural(inner) => &inner.command_function,
+            Command::ArgParser(inner) => &inner.command_function,
+        };
+        dispatch.record(|| match command_function {
+            Some(_) => TraceEvent::Executed { remaining: context.args().to_vec() },
+            None => TraceEvent::NotExecutable { remaining: context.args().to_vec() },
+        });
+        if command_function.is_some() {
+            if dispatch.refuse() {
+                return Some(Err(ChainLimitError.into()));
+            }
+            dispatch.attempts += 1;
+        }
+        let result = match command_function {
+            Some(function) if !dispatch.middleware.is_empty() => {
+                Some(Command::__intercept(function, dispatch.middleware, &dispatch.path, context))
+            }
+            _ => Command::__execute(command_function, context),
+        };
+        if let Some(Ok(())) = result {
+            dispatch.successes += 1;
+        }
+        result
+    }
+
+    /// Suggests completions for the last argument of the context, which is treated as partial.
+    pub(crate) fn __suggest(next: &Next<T>, mut context: CommandContext<T>, matching: &LiteralMatching, root: bool) -> anyhow::Result<Vec<String>> {
+        if context.args().is_empty() {
+            return Ok(Vec::new());
+        }
+        if context.args().len() == 1 {
+            if let Next::LiteralMap(sub_commands) = next {
+                let mut suggestions = matching.suggest(sub_commands, &context.args()[0], root);
+                suggestions.retain(|literal| matching.resolve(sub_commands, literal, root).is_some_and(|(_, command)| command.can_use(context.sender())));
+                return Ok(suggestions);
+            }
+        }
+        match next {
+            Next::ArgumentParser(parser_command) => {
+                match parser_command {
+                    Command::Natural(_) => unreachable!(),
+                    Command::ArgParser(_) if !parser_command.can_use(context.sender()) => Ok(Vec::new()),
+                    Command::ArgParser(parser) => {
+                        let suggestions = parser.suggest(&context, context.args().rest());
+                        match parser.parse(context) {
+                            Ok(resolved_context) if !resolved_context.args().is_empty() => {
+                                parser_command.suggest_matching(resolved_context, matching)
+                            }
+                            _ => Ok(suggestions),
+                        }
+                    }
+                }
+            }
+            Next::LiteralMap(sub_commands) => {
+                match matching.resolve(sub_commands, &context.args()[0], root) {
+                    Some((_, command)) if command.can_use(context.sender()) => {
+                        context.trim_top();
+                        command.suggest_matching(context, matching)
+                    }
+                    _ => Ok(Vec::new()),
+                }
+            }
+        }
+    }
+
+    /// Continues the remaining input at the redirect target, once per sender of the modifier.
+    fn __redirect<'a>(redirect: &'a Redirect<T>, context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        let root = dispatch.root.ok_or_else(|| anyhow::anyhow!("Cannot follow a redirect outside of an executor."))?;
+        let target = redirect::resolve_target(root, redirect.target())
+            .ok_or_else(|| anyhow::anyhow!("Unknown redirect target '{}'.", redirect.target().join(" ")))?;
+        let to_root = redirect.target().is_empty();
+        redirect::fan_out(redirect, context, dispatch, |context, dispatch| {
+            // The target is entered without matching it, so its requirements are checked here.
+            if !redirect::is_usable(root, redirect.target(), context.sender()) {
+                return Ok(None);
+            }
+            let outer = dispatch.enter_path(redirect.target());
+            let pushed = Command::__next(target, context, dispatch, to_root);
+            dispatch.restore_path(outer);
+            match pushed? {
+                Either::Left(_) => Ok(None),
+                Either::Right(pushed) => Ok(pushed),
+            }
+        })
+    }
+
+    pub fn execute_dispatch<'a>(&'a self, context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        if let Some(redirect) = self.redirect() {
+            if !context.args().is_empty() {
+                return Command::__redirect(redirect, context, dispatch);
+            }
+        }
+        match Command::__next(self.next(), context, dispatch, false)? {
+            Either::Left(passed_back) => Ok(self.run(passed_back, dispatch)),
+            Either::Right(pushed) => {
+                Ok(pushed)
+            }
+        }
+    }
+
+    pub fn suggest_matching(&self, context: CommandContext<T>, matching: &LiteralMatching) -> anyhow::Result<Vec<String>> {
+        Command::__suggest(self.next(), context, matching, false)
+    }
 }
 
 impl<T> super::CommandChildContainer<T> for Command<T> {
@@ -95,66 +424,102 @@ impl<T> super::CommandChildContainer<T> for Command<T> {
     }
 
     fn execute_context(&self, context: CommandContext<T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
-        match self {
-            Command::Natural(inner) => {
-                match Command::__next(inner.next.borrow(), context)? {
-                    Either::Left(passed_back) => {
-                        Ok(Command::__execute(&inner.command_function, passed_back))
-                    }
-                    Either::Right(pushed) => {
-                        Ok(pushed)
-                    }
-                }
-            }
-            Command::ArgParser(inner) => {
-                match Command::__next(inner.next.borrow(), context)? {
-                    Either::Left(passed_back) => {
-                        Ok(Command::__execute(&inner.command_function, passed_back))
-                    }
-                    Either::Right(pushed) => {
-                        Ok(pushed)
-                    }
-                }
-            }
-        }
+        self.execute_dispatch(context, &mut Dispatch::new(&LiteralMatching::exact()))
     }
 }
 
 pub struct NaturalCommand<T> {
     command_function: Option<CommandFunction<T>>,
+    redirect: Option<Redirect<T>>,
+    requirement: Option<Requirement<T>>,
     next: Box<Next<T>>,
 }
 
 impl<T> NaturalCommand<T> {
     pub fn executable(command_function: CommandFunction<T>) -> Self {
-        Self { command_function: Some(command_function), next: Box::new(Next::default()) }
+        Self { command_function: Some(command_function), redirect: None, requirement: None, next: Box::new(Next::default()) }
+    }
+
+    pub fn with_redirect(mut self, redirect: Redirect<T>) -> Self {
+        self.redirect = Some(redirect);
+        self
+    }
+
+    pub fn with_requirement(mut self, requirement: Requirement<T>) -> Self {
+        self.requirement = Some(requirement);
+        self
     }
 }
 
 impl<T> Default for NaturalCommand<T> {
     fn default() -> Self {
-        Self { command_function: None, next: Box::new(Next::default()) }
+        Self { command_function: None, redirect: None, requirement: None, next: Box::new(Next::default()) }
     }
 }
 
 pub struct ArgParserCommand<T> {
     command_function: Option<CommandFunction<T>>,
     identifier: String,
+    parser: Parser,
+    suggestions_type: Option<SuggestionsType>,
     parser_function: ParserFunction<T>,
+    suggestion_function: Option<SuggestionFunction<T>>,
+    redirect: Option<Redirect<T>>,
+    requirement: Option<Requirement<T>>,
     next: Box<Next<T>>,
 }
 
 impl<T> ArgParserCommand<T> {
-    pub fn non_executable(identifier: String, parser_function: ParserFunction<T>) -> Self {
-        Self { command_function: None, identifier, parser_function, next: Box::new(Next::default()) }
+    pub fn non_executable(identifier: String, parser: Parser, parser_function: ParserFunction<T>) -> Self {
+        Self { command_function: None, identifier, parser, suggestions_type: None, parser_function, suggestion_function: None, redirect: None, requirement: None, next: Box::new(Next::default()) }
     }
 
-    pub fn executable(command_function: CommandFunction<T>, identifier: String, parser_function: ParserFunction<T>) -> Self {
-        Self { command_function: Some(command_function), identifier, parser_function, next: Box::new(Next::default()) }
+    pub fn executable(command_function: CommandFunction<T>, identifier: String, parser: Parser, parser_function: ParserFunction<T>) -> Self {
+        Self { command_function: Some(command_function), identifier, parser, suggestions_type: None, parser_function, suggestion_function: None, redirect: None, requirement: None, next: Box::new(Next::default()) }
+    }
+
+    pub fn with_suggestions_type(mut self, suggestions_type: SuggestionsType) -> Self {
+        self.suggestions_type = Some(suggestions_type);
+        self
+    }
+
+    pub fn with_suggestions(mut self, suggestion_function: SuggestionFunction<T>) -> Self {
+        self.suggestion_function = Some(suggestion_function);
+        self
+    }
+
+    pub fn with_redirect(mut self, redirect: Redirect<T>) -> Self {
+        self.redirect = Some(redirect);
+        self
+    }
+
+    pub fn with_requirement(mut self, requirement: Requirement<T>) -> Self {
+        self.requirement = Some(requirement);
+        self
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub fn parser(&self) -> &Parser {
+        &self.parser
+    }
+
+    pub fn suggestions_type(&self) -> Option<&SuggestionsType> {
+        self.suggestions_type.as_ref()
+    }
+
+    /// Suggests completions for a partial argument, empty if no suggestion function is bound.
+    pub fn suggest(&self, context: &CommandContext<T>, partial: &str) -> Vec<String> {
+        match &self.suggestion_function {
+            Some(suggestion_function) => suggestion_function(context, partial),
+            None => Vec::new(),
+        }
     }
 
     pub fn parse(&self, context: CommandContext<T>) -> anyhow::Result<CommandContext<T>> {
-        let (value, mut new_context) = (&self.parser_function)(context)?;
+        let (value, mut new_context) = (self.parser_function)(context)?;
         new_context.value_arg((self.identifier.clone(), value));
         Ok(new_context)
     }
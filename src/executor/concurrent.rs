@@ -0,0 +1,138 @@
+use crate::executor::context::CommandContext;
+use crate::executor::parse::ParseResults;
+use crate::executor::{CommandChildContainer, Executor};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Cloneable handle which runs commands of a shared executor from async tasks, e.g. one task per
+/// connected player.
+///
+/// Command functions are synchronous, so every dispatch runs on tokio's blocking pool instead of
+/// stalling the task that requested it. Dispatches run in parallel, optionally bounded by a
+/// concurrency limit.
+pub struct Dispatcher<T> {
+    executor: Arc<Executor<T>>,
+    permits: Option<Arc<Semaphore>>,
+}
+
+impl<T> Clone for Dispatcher<T> {
+    fn clone(&self) -> Self {
+        Self { executor: Arc::clone(&self.executor), permits: self.permits.clone() }
+    }
+}
+
+impl<T> From<Executor<T>> for Dispatcher<T> {
+    fn from(executor: Executor<T>) -> Self {
+        Self::new(Arc::new(executor))
+    }
+}
+
+impl<T> Dispatcher<T> {
+    pub fn new(executor: Arc<Executor<T>>) -> Self {
+        Self { executor, permits: None }
+    }
+
+    /// Limits how many commands run at the same time; further dispatches wait for a free slot.
+    pub fn with_concurrency_limit(mut self, limit: usize) -> Self {
+        self.permits = Some(Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    pub fn executor(&self) -> &Arc<Executor<T>> {
+        &self.executor
+    }
+}
+
+impl<T: Send + 'static> Dispatcher<T> {
+    async fn permit(&self) -> anyhow::Result<Option<OwnedSemaphorePermit>> {
+        match &self.permits {
+            Some(permits) => Ok(Some(Arc::clone(permits).acquire_owned().await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Executes the context on the blocking pool.
+    ///
+    /// Dropping the returned future doesn't cancel a command which already started.
+    pub async fn dispatch(&self, context: CommandContext<T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        let permit = self.permit().await?;
+        let executor = Arc::clone(&self.executor);
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            executor.execute_context(context)
+        })
+        .await?
+    }
+
+    /// Splits raw input such as `give @p diamond` and executes it for the sender.
+    pub async fn dispatch_input(&self, sender: T, input: &str) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        self.dispatch(CommandContext::from_input(sender, input)).await
+    }
+
+    /// Executes cached parse results for the sender on the blocking pool.
+    pub async fn execute(&self, results: Arc<ParseResults>, sender: T) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        let permit = self.permit().await?;
+        let executor = Arc::clone(&self.executor);
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            executor.execute(&results, sender)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{Command, NaturalCommand};
+    use crate::executor::concurrent::Dispatcher;
+    use crate::executor::context::CommandContext;
+    use crate::executor::{CommandChildContainer, Executor};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+
+    #[test]
+    pub fn test_concurrent_dispatch() -> anyhow::Result<()> {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (now, most) = (running.clone(), peak.clone());
+        // The first two commands meet here, which they only can when both run at once.
+        let rendezvous = Arc::new(Barrier::new(2));
+        let started = AtomicUsize::new(0);
+        let mut executor = Executor::<String>::new();
+        executor.child(
+            "work",
+            Command::Natural(NaturalCommand::executable(Box::new(move |_| {
+                most.fetch_max(now.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                if started.fetch_add(1, Ordering::SeqCst) < 2 {
+                    rendezvous.wait();
+                }
+                now.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }))),
+        )?;
+        let dispatcher = Dispatcher::from(executor).with_concurrency_limit(2);
+        let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+
+        let results = runtime.block_on(async {
+            let tasks = (0..8)
+                .map(|player| {
+                    let dispatcher = dispatcher.clone();
+                    tokio::spawn(async move { dispatcher.dispatch_input(format!("player {}", player), "work").await })
+                })
+                .collect::<Vec<_>>();
+            let mut results = Vec::new();
+            for task in tasks {
+                results.push(task.await?);
+            }
+            anyhow::Ok(results)
+        })?;
+        for result in results {
+            result?.expect("work should be executable")?;
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        let parsed = Arc::new(dispatcher.executor().parse(CommandContext::from_input("server".into(), "work"))?);
+        runtime.block_on(dispatcher.execute(parsed, "player".into()))?.expect("work should be executable")?;
+        Ok(())
+    }
+}
@@ -1,9 +1,37 @@
-#[derive(Debug)]
+use crate::executor::extensions::Extensions;
+use std::ops::{Deref, DerefMut, Index};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     String(String),
     Integer(u32),
     Float(f32),
     Generic(String),
+    Entity(Box<crate::arguments::entity::EntitySelector>),
+    ScoreHolder(crate::arguments::entity::ScoreHolder),
+    Coordinates(crate::arguments::coordinates::Coordinates),
+    Rotation(crate::arguments::coordinates::Rotation),
+    IntRange(crate::arguments::range::MinMaxBounds<i32>),
+    FloatRange(crate::arguments::range::MinMaxBounds<f64>),
+    Nbt(crate::arguments::nbt::NbtTag),
+    NbtPath(crate::arguments::nbt::NbtPath),
+    Block(crate::arguments::block::BlockState),
+    Item(crate::arguments::block::ItemStack),
+    Component(Box<crate::arguments::chat::TextComponent>),
+    Message(crate::arguments::chat::Message),
+    Time(u32),
+    Angle(crate::arguments::coordinates::Angle),
+    Swizzle(crate::arguments::coordinates::Swizzle),
+    Operation(crate::arguments::scoreboard::Operation),
+    ScoreboardSlot(crate::arguments::scoreboard::ScoreboardSlot),
+    EntityAnchor(crate::arguments::entity::EntityAnchor),
+    ItemSlot(i32),
+    Color(crate::arguments::misc::Color),
+    Identifier(crate::arguments::identifier::ResourceLocation),
+    Function(crate::arguments::block::ResourceTarget),
+    Uuid(u128),
+    GameProfile(crate::arguments::profile::ProfileTarget),
 }
 
 #[macro_export]
@@ -15,7 +43,7 @@ macro_rules! unwrap_value {
 
 pub type ArgValue = (String, Value);
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ArgValues {
     values: Vec<ArgValue>,
 }
@@ -32,35 +60,202 @@ impl ArgValues {
     pub fn borrow_inner(&self) -> &Vec<ArgValue> {
         &self.values
     }
+
+    pub fn get(&self, identifier: &str) -> Option<&Value> {
+        self.values
+            .iter()
+            .find(|(value_identifier, _)| value_identifier == identifier)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Remaining tokens of the input.
+///
+/// The input is stored once as a single string shared by every context of a dispatch, so
+/// cloning the arguments and consuming a token never copies the tokens themselves. The string is
+/// an `Arc<str>` rather than a borrowed `&str`: a borrow would put a lifetime on `CommandContext`
+/// and every command and parser function, and contexts have to outlive the input for forked
+/// redirects, cached `ParseResults` and the blocking pool of the `Dispatcher`.
+#[derive(Debug, Clone)]
+pub struct Args {
+    source: Arc<str>,
+    spans: Arc<[(usize, usize)]>,
+    cursor: usize,
+}
+
+impl Args {
+    pub fn from_tokens<I: IntoIterator<Item = S>, S: AsRef<str>>(tokens: I) -> Self {
+        let mut source = String::new();
+        let mut spans = Vec::new();
+        for (index, token) in tokens.into_iter().enumerate() {
+            if index > 0 {
+                source.push(' ');
+            }
+            let start = source.len();
+            source.push_str(token.as_ref());
+            spans.push((start, source.len()));
+        }
+        Self { source: source.into(), spans: spans.into(), cursor: 0 }
+    }
+
+    /// Splits raw input on single spaces, the same way a chat command is split.
+    pub fn parse(input: &str) -> Self {
+        let mut spans = Vec::new();
+        let mut start = 0;
+        for (index, _) in input.match_indices(' ') {
+            spans.push((start, index));
+            start = index + 1;
+        }
+        spans.push((start, input.len()));
+        Self { source: input.into(), spans: spans.into(), cursor: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len() - self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.spans.get(self.cursor + index).map(|(start, end)| &self.source[*start..*end])
+    }
+
+    pub fn first(&self) -> Option<&str> {
+        self.get(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.spans[self.cursor..].iter().map(move |(start, end)| &self.source[*start..*end])
+    }
+
+    /// The remaining tokens joined by spaces, borrowed from the input.
+    pub fn rest(&self) -> &str {
+        match self.spans.get(self.cursor) {
+            Some((start, _)) => &self.source[*start..],
+            None => "",
+        }
+    }
+
+    /// The whole input, including the tokens which were already consumed.
+    pub fn input(&self) -> &Arc<str> {
+        &self.source
+    }
+
+    /// The tokens which were already consumed, joined by spaces.
+    pub fn consumed(&self) -> &str {
+        match self.spans.get(self.cursor) {
+            Some((start, _)) => self.source[..*start].trim_end_matches(' '),
+            None => &self.source,
+        }
+    }
+
+    /// Number of remaining tokens which start before the byte offset into `rest()`.
+    pub fn count_before(&self, offset: usize) -> usize {
+        let base = match self.spans.get(self.cursor) {
+            Some((start, _)) => *start,
+            None => return 0,
+        };
+        self.spans[self.cursor..].iter().take_while(|(start, _)| start - base < offset).count()
+    }
+
+    pub fn to_vec(&self) -> Vec<String> {
+        self.iter().map(String::from).collect()
+    }
+
+    fn advance(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.spans.len());
+    }
+}
+
+impl Index<usize> for Args {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        self.get(index).expect("Argument index out of bounds.")
+    }
+}
+
+impl PartialEq for Args {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+/// Editable copy of the remaining tokens, written back to the context when dropped.
+pub struct ArgsMut<'a> {
+    args: &'a mut Args,
+    tokens: Vec<String>,
+}
+
+impl Deref for ArgsMut<'_> {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Vec<String> {
+        &self.tokens
+    }
+}
+
+impl DerefMut for ArgsMut<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<String> {
+        &mut self.tokens
+    }
 }
 
+impl Drop for ArgsMut<'_> {
+    fn drop(&mut self) {
+        *self.args = Args::from_tokens(std::mem::take(&mut self.tokens));
+    }
+}
+
+/// Input and parsed values of a single execution.
+///
+/// The remaining input is an `Args` rather than a `Vec<String>`: `args()` and `split()` return
+/// `Args`, which indexes to `&str` and converts with `to_vec()`, and `args_mut()` hands out a
+/// guard instead of `&mut Vec<String>`.
 #[derive(Debug)]
 pub struct CommandContext<T> {
     sender: T,
-    args: Vec<String>,
+    args: Args,
     value_args: ArgValues,
+    extensions: Arc<Extensions>,
 }
 
 impl<T> CommandContext<T> {
-    pub fn create<S: Into<T>, VS: Into<String>>(sender: S, split_args: Vec<VS>) -> Self {
-        Self::new(
-            sender.into(),
-            split_args
-                .into_iter()
-                .map(|item| item.into())
-                .collect::<Vec<String>>(),
-        )
+    pub fn create<S: Into<T>, VS: AsRef<str>>(sender: S, split_args: Vec<VS>) -> Self {
+        Self::from_args(sender.into(), Args::from_tokens(split_args))
     }
 
     pub fn new(sender: T, args: Vec<String>) -> Self {
-        Self { sender, args, value_args: ArgValues::new() }
+        Self::from_args(sender, Args::from_tokens(args))
+    }
+
+    /// Context for raw input such as `give @p diamond`, without allocating a string per token.
+    pub fn from_input(sender: T, input: &str) -> Self {
+        Self::from_args(sender, Args::parse(input))
+    }
+
+    pub fn from_args(sender: T, args: Args) -> Self {
+        Self { sender, args, value_args: ArgValues::new(), extensions: Arc::default() }
+    }
+
+    /// Adds state for this execution only; it takes precedence over the executor's state.
+    pub fn with_state<S: Send + Sync + 'static>(mut self, state: S) -> Self {
+        Arc::make_mut(&mut self.extensions).insert(state);
+        self
+    }
+
+    /// Copies the remaining input and state for another sender, without the parsed values.
+    pub fn redirected(&self, sender: T) -> Self {
+        Self { sender, args: self.args.clone(), value_args: ArgValues::new(), extensions: self.extensions.clone() }
     }
 
     pub fn sender(&self) -> &T {
         &self.sender
     }
 
-    pub fn args(&self) -> &Vec<String> {
+    pub fn args(&self) -> &Args {
         &self.args
     }
 
@@ -68,23 +263,74 @@ impl<T> CommandContext<T> {
         &mut self.sender
     }
 
-    pub fn args_mut(&mut self) -> &mut Vec<String> {
-        &mut self.args
+    /// Remaining tokens as an editable list, written back when the guard is dropped.
+    ///
+    /// Copies every remaining token; parsers on the hot path should use `trim_top` instead.
+    pub fn args_mut(&mut self) -> ArgsMut<'_> {
+        let tokens = self.args.to_vec();
+        ArgsMut { args: &mut self.args, tokens }
     }
 
-    pub fn split(self) -> (T, Vec<String>) {
+    pub fn split(self) -> (T, Args) {
         (self.sender, self.args)
     }
 
-    pub fn value_arg(&mut self, value: ArgValue) {
-        self.value_args.values.push(value);
+    pub fn value_args(&self) -> &ArgValues {
+        &self.value_args
+    }
+
+    pub fn value(&self, identifier: &str) -> Option<&Value> {
+        self.value_args.get(identifier)
+    }
+
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// State of the given type, from either the context or the executing executor.
+    pub fn state<S: Send + Sync + 'static>(&self) -> anyhow::Result<&S> {
+        self.extensions
+            .get()
+            .ok_or_else(|| anyhow::anyhow!("No state of type {} is available", std::any::type_name::<S>()))
+    }
+
+    pub(crate) fn attach_extensions(&mut self, fallback: &Arc<Extensions>) {
+        if self.extensions.is_empty() {
+            self.extensions = fallback.clone();
+        } else if !fallback.is_empty() {
+            Arc::make_mut(&mut self.extensions).extend_missing(fallback);
+        }
+    }
+
+    pub fn value_arg<I: Into<String>>(&mut self, (identifier, value): (I, Value)) {
+        self.value_args.values.push((identifier.into(), value));
+    }
+
+    pub(crate) fn take_values(&mut self) -> ArgValues {
+        std::mem::take(&mut self.value_args)
     }
 
     pub fn trim_top(&mut self) {
-        self.args.remove(0);
+        self.args.advance();
     }
 
     pub fn overwrite_args(&mut self, new_args: Vec<String>) {
-        self.args = new_args;
+        self.args = Args::from_tokens(new_args);
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use crate::executor::context::{CommandContext, Value};
+
+    #[test]
+    pub fn test_args_mut() {
+        let mut context = CommandContext::from_input((), "give @p diamond");
+        context.trim_top();
+        context.args_mut().push("64".to_string());
+        assert_eq!(context.args().to_vec(), vec!["@p", "diamond", "64"]);
+        assert_eq!(&context.args()[2], "64");
+
+        context.value_arg(("count".to_string(), Value::Integer(64)));
+        assert_eq!(context.value("count"), Some(&Value::Integer(64)));
+    }
+}
@@ -0,0 +1,251 @@
+use crate::executor::context::CommandContext;
+use crate::executor::middleware::{Invocation, Middleware, SenderIdentity};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Time which has to pass between two executions.
+    Cooldown(Duration),
+    /// Token bucket allowing bursts of up to `capacity` executions, refilled by one token every
+    /// `refill`.
+    RateLimit { capacity: u32, refill: Duration },
+}
+
+/// Error returned in place of running a command which is still limited for the sender.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CooldownError {
+    command: String,
+    remaining: Duration,
+}
+
+impl CooldownError {
+    /// Path of the limited node, e.g. `tp` or `give targets`.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /// Remaining time rounded up to whole seconds.
+    pub fn remaining_seconds(&self) -> u64 {
+        self.remaining.as_secs() + u64::from(self.remaining.subsec_nanos() > 0)
+    }
+}
+
+impl Display for CooldownError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let seconds = self.remaining_seconds();
+        write!(f, "This command is on cooldown, {} second{} remaining", seconds, if seconds == 1 { "" } else { "s" })
+    }
+}
+
+impl std::error::Error for CooldownError {}
+
+#[derive(Debug, Clone, Copy)]
+enum Usage {
+    Cooldown { ready_at: Instant },
+    Bucket { tokens: f64, updated: Instant },
+}
+
+impl Usage {
+    fn refilled(self, limit: Limit, now: Instant) -> Self {
+        match (self, limit) {
+            (Usage::Bucket { tokens, updated }, Limit::RateLimit { capacity, refill }) => {
+                let gained = now.saturating_duration_since(updated).as_secs_f64() / refill.as_secs_f64();
+                Usage::Bucket { tokens: (tokens + gained).min(f64::from(capacity)), updated: now }
+            }
+            (usage, _) => usage,
+        }
+    }
+
+    fn remaining(self, now: Instant, limit: Limit) -> Option<Duration> {
+        match (self, limit) {
+            (Usage::Cooldown { ready_at }, _) if ready_at > now => Some(ready_at - now),
+            (Usage::Bucket { tokens, .. }, Limit::RateLimit { refill, .. }) if tokens < 1.0 => Some(refill.mul_f64(1.0 - tokens)),
+            _ => None,
+        }
+    }
+
+    fn is_expired(self, now: Instant, limit: Limit) -> bool {
+        match (self, limit) {
+            (Usage::Cooldown { ready_at }, _) => ready_at <= now,
+            (Usage::Bucket { tokens, .. }, Limit::RateLimit { capacity, .. }) => tokens >= f64::from(capacity),
+            (Usage::Bucket { .. }, Limit::Cooldown(_)) => true,
+        }
+    }
+}
+
+/// Middleware enforcing per-sender cooldowns and rate limits on parts of the tree.
+///
+/// A limit applies to every execution whose matched path starts with the path it was added for,
+/// so a limit on `tp` covers all of its variants and is shared between them. Only executions which
+/// ran and succeeded use up a limit; vetoed and failing ones leave it untouched.
+#[derive(Debug, Default)]
+pub struct Cooldowns {
+    limits: Vec<(Vec<String>, Limit)>,
+    usage: Mutex<HashMap<(String, usize), Usage>>,
+}
+
+impl Cooldowns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a limit for the node at the space separated path, e.g. `give targets`.
+    ///
+    /// Fails for a rate limit without capacity, which could never let a command through.
+    pub fn with_limit(mut self, path: &str, limit: Limit) -> anyhow::Result<Self> {
+        if let Limit::RateLimit { capacity: 0, .. } = limit {
+            anyhow::bail!("Rate limit for {} needs a capacity of at least 1.", path);
+        }
+        self.limits.push((path.split(' ').map(String::from).collect(), limit));
+        Ok(self)
+    }
+
+    pub fn cooldown(mut self, path: &str, cooldown: Duration) -> Self {
+        self.limits.push((path.split(' ').map(String::from).collect(), Limit::Cooldown(cooldown)));
+        self
+    }
+
+    pub fn rate_limit(self, path: &str, capacity: u32, refill: Duration) -> anyhow::Result<Self> {
+        self.with_limit(path, Limit::RateLimit { capacity, refill })
+    }
+
+    fn usage(&self) -> anyhow::Result<std::sync::MutexGuard<'_, HashMap<(String, usize), Usage>>> {
+        self.usage.lock().map_err(|_| anyhow::anyhow!("Cooldown lock was poisoned."))
+    }
+
+    fn limits_of<'a>(&'a self, path: &'a [&str]) -> impl Iterator<Item = (usize, &'a (Vec<String>, Limit))> + 'a {
+        self.limits
+            .iter()
+            .enumerate()
+            .filter(move |(_, (prefix, _))| prefix.len() <= path.len() && prefix.iter().zip(path).all(|(expected, segment)| expected == segment))
+    }
+
+    /// Fails if one of the limits of the path hasn't passed yet for the sender, without using it up.
+    pub fn check_at(&self, identity: &str, path: &[&str], now: Instant) -> anyhow::Result<()> {
+        let limits = self.limits_of(path).collect::<Vec<_>>();
+        if limits.is_empty() {
+            return Ok(());
+        }
+        let usage = self.usage()?;
+        let mut blocked: Option<CooldownError> = None;
+        for (index, (prefix, limit)) in &limits {
+            let current = usage.get(&(identity.to_string(), *index)).map(|usage| usage.refilled(*limit, now));
+            if let Some(remaining) = current.and_then(|usage| usage.remaining(now, *limit)) {
+                if blocked.as_ref().is_none_or(|blocked| blocked.remaining < remaining) {
+                    blocked = Some(CooldownError { command: prefix.join(" "), remaining });
+                }
+            }
+        }
+        match blocked {
+            Some(blocked) => Err(blocked.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Records an execution of the path by the sender, using up its cooldowns and a token of its
+    /// rate limits.
+    pub fn record_at(&self, identity: &str, path: &[&str], now: Instant) -> anyhow::Result<()> {
+        let limits = self.limits_of(path).collect::<Vec<_>>();
+        if limits.is_empty() {
+            return Ok(());
+        }
+        let mut usage = self.usage()?;
+        for (index, (_, limit)) in limits {
+            let key = (identity.to_string(), index);
+            let next = match (usage.get(&key).map(|usage| usage.refilled(*limit, now)), *limit) {
+                (_, Limit::Cooldown(cooldown)) => Usage::Cooldown { ready_at: now + cooldown },
+                (Some(Usage::Bucket { tokens, .. }), Limit::RateLimit { .. }) => Usage::Bucket { tokens: tokens - 1.0, updated: now },
+                (_, Limit::RateLimit { capacity, .. }) => Usage::Bucket { tokens: f64::from(capacity) - 1.0, updated: now },
+            };
+            usage.insert(key, next);
+        }
+        Ok(())
+    }
+
+    /// Clears every cooldown and rate limit of the sender.
+    pub fn reset(&self, identity: &str) -> anyhow::Result<()> {
+        self.usage()?.retain(|(owner, _), _| owner != identity);
+        Ok(())
+    }
+
+    /// Drops the state of limits which have fully recovered, to bound memory on busy servers.
+    pub fn prune(&self) -> anyhow::Result<()> {
+        let now = Instant::now();
+        let limits = &self.limits;
+        self.usage()?.retain(|(_, index), usage| !usage.refilled(limits[*index].1, now).is_expired(now, limits[*index].1));
+        Ok(())
+    }
+}
+
+/// Identity of the sender an invocation is charged to once it succeeds.
+struct Charged(String);
+
+impl<T: SenderIdentity> Middleware<T> for Cooldowns {
+    fn before(&self, invocation: &mut Invocation, context: &CommandContext<T>) -> anyhow::Result<()> {
+        let identity = context.sender().identity();
+        self.check_at(&identity, invocation.path(), Instant::now())?;
+        invocation.state_mut().insert(Charged(identity));
+        Ok(())
+    }
+
+    fn after(&self, invocation: &Invocation, result: anyhow::Result<()>) -> anyhow::Result<()> {
+        if let (true, Ok(()), Some(Charged(identity))) = (invocation.is_executed(), &result, invocation.state().get::<Charged>()) {
+            self.record_at(identity, invocation.path(), Instant::now())?;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{Command, NaturalCommand};
+    use crate::executor::context::CommandContext;
+    use crate::executor::cooldown::{CooldownError, Cooldowns};
+    use crate::executor::{CommandChildContainer, Executor};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    pub fn test_cooldowns() -> anyhow::Result<()> {
+        let mut executor = Executor::<String>::new().with_middleware(Cooldowns::new().cooldown("heal", Duration::from_secs(30)));
+        executor.child("heal", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+        executor.execute_context(CommandContext::create("Alex", vec!["heal"]))?.unwrap()?;
+        executor.execute_context(CommandContext::create("Steve", vec!["heal"]))?.unwrap()?;
+
+        let error = executor.execute_context(CommandContext::create("Alex", vec!["heal"]))?.unwrap().unwrap_err();
+        let cooldown = error.downcast_ref::<CooldownError>().expect("the error should be structured");
+        assert_eq!((cooldown.command(), cooldown.remaining_seconds()), ("heal", 30));
+        assert_eq!(error.to_string(), "This command is on cooldown, 30 seconds remaining");
+
+        let mut executor = Executor::<String>::new().with_middleware(Cooldowns::new().cooldown("feed", Duration::from_secs(30)));
+        executor.child(
+            "feed",
+            Command::Natural(NaturalCommand::executable(Box::new(|context| match context.sender().as_str() {
+                "Alex" => anyhow::bail!("Alex is not hungry"),
+                _ => Ok(()),
+            }))),
+        )?;
+        for _ in 0..2 {
+            let error = executor.execute_context(CommandContext::create("Alex", vec!["feed"]))?.unwrap().unwrap_err();
+            assert_eq!(error.to_string(), "Alex is not hungry");
+        }
+
+        let limits = Cooldowns::new().rate_limit("tp", 2, Duration::from_secs(10))?;
+        let start = Instant::now();
+        limits.check_at("Alex", &["tp", "target"], start)?;
+        limits.check_at("Alex", &["tp", "target"], start)?;
+        limits.record_at("Alex", &["tp", "target"], start)?;
+        limits.record_at("Alex", &["tp", "location"], start)?;
+        assert!(limits.check_at("Alex", &["tp", "target"], start + Duration::from_secs(5)).is_err());
+        limits.check_at("Alex", &["tp", "target"], start + Duration::from_secs(10))?;
+        limits.check_at("Alex", &["tpa"], start)?;
+        assert!(Cooldowns::new().rate_limit("tp", 0, Duration::from_secs(10)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+type Extension = (&'static str, Arc<dyn Any + Send + Sync>);
+
+/// Typed map of state handed to handlers at execution time, keyed by the type of the state.
+///
+/// An executor's extensions are attached to every context it executes, so handlers can read
+/// server state such as the world or player list without capturing it in each closure.
+#[derive(Default, Clone)]
+pub struct Extensions {
+    map: HashMap<TypeId, Extension>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the state, replacing any previous state of the same type.
+    pub fn insert<S: Send + Sync + 'static>(&mut self, state: S) {
+        self.insert_arc(Arc::new(state));
+    }
+
+    /// Inserts state which is also shared outside of the executor.
+    pub fn insert_arc<S: Send + Sync + 'static>(&mut self, state: Arc<S>) {
+        self.map.insert(TypeId::of::<S>(), (std::any::type_name::<S>(), state));
+    }
+
+    pub fn get<S: Send + Sync + 'static>(&self) -> Option<&S> {
+        self.map.get(&TypeId::of::<S>()).and_then(|(_, state)| state.downcast_ref())
+    }
+
+    pub fn contains<S: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<S>())
+    }
+
+    pub fn remove<S: Send + Sync + 'static>(&mut self) -> bool {
+        self.map.remove(&TypeId::of::<S>()).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Adds every entry of the fallback which isn't already present.
+    pub fn extend_missing(&mut self, fallback: &Extensions) {
+        for (type_id, extension) in &fallback.map {
+            self.map.entry(*type_id).or_insert_with(|| extension.clone());
+        }
+    }
+}
+
+impl Debug for Extensions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.map.values().map(|(name, _)| name)).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{Command, NaturalCommand};
+    use crate::executor::context::CommandContext;
+    use crate::executor::{CommandChildContainer, Executor};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Counter(AtomicU32);
+
+    struct Motd(&'static str);
+
+    #[test]
+    pub fn test_state() -> anyhow::Result<()> {
+        let mut executor = Executor::<()>::new().with_state(Counter(AtomicU32::new(0))).with_state(Motd("executor"));
+        executor.child(
+            "count",
+            Command::Natural(NaturalCommand::executable(Box::new(|context| {
+                context.state::<Counter>()?.0.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(context.state::<Motd>()?.0, "context");
+                Ok(())
+            }))),
+        )?;
+
+        let context = CommandContext::create((), vec!["count"]).with_state(Motd("context"));
+        executor.execute_context(context)?.unwrap()?;
+        assert_eq!(executor.extensions().get::<Counter>().map(|counter| counter.0.load(Ordering::SeqCst)), Some(1));
+        assert!(CommandContext::<()>::create((), vec!["count"]).state::<Counter>().is_err());
+        Ok(())
+    }
+}
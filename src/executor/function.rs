@@ -0,0 +1,329 @@
+use crate::arguments::nbt::{NbtCompound, NbtTag};
+use crate::executor::context::CommandContext;
+use crate::executor::parse::ParseResults;
+use crate::executor::Executor;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+/// Invocation of a single line of a function file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionEntry {
+    /// Command which is matched and parsed once, when the function is loaded.
+    Command(ParseResults),
+    /// `$` line whose `$(name)` variables are substituted from the macro arguments on every run.
+    Macro { template: String, variables: Vec<String> },
+}
+
+/// Parsed line of a function file together with its 1-based line number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionLine {
+    line: usize,
+    entry: FunctionEntry,
+}
+
+impl FunctionLine {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn entry(&self) -> &FunctionEntry {
+        &self.entry
+    }
+
+    /// Parse results of the command this line runs; macro lines are substituted from the
+    /// arguments and parsed for the sender on every run.
+    pub fn results<T>(&self, executor: &Executor<T>, sender: T, arguments: Option<&NbtCompound>) -> anyhow::Result<Cow<'_, ParseResults>> {
+        match &self.entry {
+            FunctionEntry::Command(results) => Ok(Cow::Borrowed(results)),
+            FunctionEntry::Macro { template, variables } => {
+                let arguments = arguments.ok_or_else(|| anyhow::anyhow!("Cannot run a macro line without arguments"))?;
+                let missing = variables.iter().filter(|variable| !arguments.contains_key(*variable)).cloned().collect::<Vec<String>>();
+                if !missing.is_empty() {
+                    anyhow::bail!("Missing macro arguments: {}", missing.join(", "));
+                }
+                // A single pass, so `$(...)` inside a substituted value is never expanded again.
+                let mut command = String::with_capacity(template.len());
+                let mut rest = template.as_str();
+                while let Some(start) = rest.find("$(") {
+                    let end = start + rest[start..].find(')').expect("Macro variables are validated when parsing.");
+                    command.push_str(&rest[..start]);
+                    match &arguments[&rest[start + 2..end]] {
+                        NbtTag::String(value) => command.push_str(value),
+                        tag => command.push_str(&tag.to_string()),
+                    }
+                    rest = &rest[end + 1..];
+                }
+                command.push_str(rest);
+                parse_command(executor, sender, &command).map(Cow::Owned)
+            }
+        }
+    }
+}
+
+/// Parses a command, failing unless it reaches a command function.
+fn parse_command<T>(executor: &Executor<T>, sender: T, command: &str) -> anyhow::Result<ParseResults> {
+    let results = executor.parse(CommandContext::from_input(sender, command))?;
+    if !results.is_executable() {
+        anyhow::bail!("Unknown or incomplete command '{}'", command);
+    }
+    Ok(results)
+}
+
+/// Error of a single line of a function, either while parsing or running it.
+#[derive(Debug)]
+pub struct LineError {
+    line: usize,
+    error: anyhow::Error,
+}
+
+impl LineError {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn error(&self) -> &anyhow::Error {
+        &self.error
+    }
+}
+
+impl Display for LineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for LineError {}
+
+fn macro_variables(line: usize, template: &str) -> anyhow::Result<Vec<String>> {
+    let mut variables = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("$(") {
+        rest = &rest[start + 2..];
+        let end = rest.find(')').ok_or_else(|| anyhow::anyhow!("Unterminated macro variable on line {}", line))?;
+        let name = &rest[..end];
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            anyhow::bail!("Invalid macro variable name '{}' on line {}", name, line);
+        }
+        if !variables.iter().any(|variable| variable == name) {
+            variables.push(name.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+    if variables.is_empty() {
+        anyhow::bail!("No variables in macro on line {}", line);
+    }
+    Ok(variables)
+}
+
+/// `.mcfunction` file whose commands were parsed against an executor when it was loaded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Function {
+    lines: Vec<FunctionLine>,
+}
+
+impl Function {
+    /// Parses function source against the executor for the sender, skipping blank lines and `#`
+    /// comments; fails with the first line which is unknown or doesn't parse.
+    ///
+    /// Lines ending with a backslash continue on the next line, like vanilla. Macro lines are only
+    /// checked for their variables, since they are parsed once substituted.
+    pub fn parse<T: Clone>(source: &str, executor: &Executor<T>, sender: T) -> Result<Self, LineError> {
+        let mut lines = Vec::new();
+        let mut source_lines = source.lines().enumerate().map(|(index, text)| (index + 1, text.trim()));
+        while let Some((line, text)) = source_lines.next() {
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let mut text = text.to_string();
+            while let Some(continued) = text.strip_suffix('\\') {
+                let (_, next) = source_lines
+                    .next()
+                    .ok_or_else(|| LineError { line, error: anyhow::anyhow!("Line continuation at end of file") })?;
+                text = format!("{}{}", continued, next);
+            }
+            let entry = if let Some(template) = text.strip_prefix('$') {
+                let variables = macro_variables(line, template).map_err(|error| LineError { line, error })?;
+                FunctionEntry::Macro { template: template.to_string(), variables }
+            } else if let Some(command) = text.strip_prefix('/') {
+                let error = if command.starts_with('/') {
+                    anyhow::anyhow!("Unknown or invalid command '{}' on line {} (if you intended to make a comment, use '#' not '//')", text, line)
+                } else {
+                    anyhow::anyhow!("Unknown or invalid command '{}' on line {} (did you mean '{}'? Do not use a preceding forwards slash.)", text, line, command)
+                };
+                return Err(LineError { line, error });
+            } else {
+                FunctionEntry::Command(parse_command(executor, sender.clone(), &text).map_err(|error| LineError { line, error })?)
+            };
+            lines.push(FunctionLine { line, entry });
+        }
+        Ok(Self { lines })
+    }
+
+    pub fn lines(&self) -> &Vec<FunctionLine> {
+        &self.lines
+    }
+
+    pub fn is_macro(&self) -> bool {
+        self.lines.iter().any(|line| matches!(line.entry, FunctionEntry::Macro { .. }))
+    }
+}
+
+/// Error of a command which wasn't run because the command chain limit was reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainLimitError;
+
+impl Display for ChainLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Command chain limit reached")
+    }
+}
+
+impl std::error::Error for ChainLimitError {}
+
+/// Outcome of running a function; failing lines don't stop the function, like vanilla.
+#[derive(Debug, Default)]
+pub struct FunctionResult {
+    executed: usize,
+    attempted: usize,
+    errors: Vec<LineError>,
+    truncated: bool,
+}
+
+impl FunctionResult {
+    /// Number of commands which ran successfully, counting every forked sender.
+    pub fn executed(&self) -> usize {
+        self.executed
+    }
+
+    /// Number of commands counted against the command chain limit: every command function which
+    /// was started, and every line which failed before reaching one.
+    pub fn attempted(&self) -> usize {
+        self.attempted
+    }
+
+    pub fn errors(&self) -> &Vec<LineError> {
+        &self.errors
+    }
+
+    /// Whether the command chain limit stopped the function before its last line.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// Runs functions against an executor, line by line.
+#[derive(Debug, Clone)]
+pub struct FunctionRunner {
+    max_command_chain: usize,
+}
+
+impl Default for FunctionRunner {
+    fn default() -> Self {
+        Self { max_command_chain: 65536 }
+    }
+}
+
+impl FunctionRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many commands a single run may attempt, like the `maxCommandChainLength` game rule.
+    ///
+    /// Failing commands and every sender of a forked line count against the limit.
+    pub fn with_max_command_chain(mut self, max_command_chain: usize) -> Self {
+        self.max_command_chain = max_command_chain;
+        self
+    }
+
+    pub fn max_command_chain(&self) -> usize {
+        self.max_command_chain
+    }
+
+    /// Runs every line of the function for the sender, substituting macro lines from the arguments.
+    pub fn run<T: Clone>(&self, executor: &Executor<T>, function: &Function, sender: T, arguments: Option<&NbtCompound>) -> FunctionResult {
+        let mut result = FunctionResult::default();
+        for line in &function.lines {
+            let remaining = self.max_command_chain.saturating_sub(result.attempted);
+            if remaining == 0 {
+                result.truncated = true;
+                break;
+            }
+            let (outcome, attempted, limited) = match line.results(executor, sender.clone(), arguments) {
+                Ok(results) => executor.execute_limited(&results, sender.clone(), remaining),
+                Err(error) => (Err(error), 0, false),
+            };
+            result.attempted += attempted.max(1);
+            match outcome {
+                Ok(executed) => result.executed += executed,
+                Err(error) if error.is::<ChainLimitError>() => {}
+                Err(error) => result.errors.push(LineError { line: line.line, error }),
+            }
+            if limited {
+                result.truncated = true;
+                break;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::misc::color_parser;
+    use crate::arguments::nbt::{NbtCompound, NbtTag};
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand};
+    use crate::executor::function::{Function, FunctionRunner};
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::protocol::Parser;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    pub fn test_function_runner() -> anyhow::Result<()> {
+        let said = Arc::new(Mutex::new(Vec::new()));
+        let log = said.clone();
+        let mut executor = Executor::<String>::new();
+        executor.child(
+            "say",
+            Command::Natural(NaturalCommand::executable(Box::new(move |context| {
+                log.lock().unwrap().push(format!("{}: {}", context.sender(), context.args().rest()));
+                Ok(())
+            }))),
+        )?;
+        executor.child("fail", Command::Natural(NaturalCommand::executable(Box::new(|_| anyhow::bail!("Failed")))))?;
+        let mut color = Command::Natural(NaturalCommand::default());
+        color.child("value", Command::ArgParser(ArgParserCommand::executable(Box::new(|_| Ok(())), "value".into(), Parser::Color, color_parser())))?;
+        executor.child("color", color)?;
+        let parse = |source: &str| Function::parse(source, &executor, "Server".to_string());
+
+        let source = "# greet everyone\n\nsay hello\nfail\n$say hi $(name)\nsay \\\n  bye\n";
+        let function = parse(source).map_err(|error| anyhow::anyhow!("{}", error))?;
+        assert_eq!(function.lines().len(), 4);
+        assert!(function.is_macro());
+
+        let arguments = NbtCompound::from([("name".to_string(), NbtTag::String("Steve".into()))]);
+        let result = FunctionRunner::new().run(&executor, &function, "Server".to_string(), Some(&arguments));
+        assert_eq!(result.executed(), 3);
+        assert_eq!(result.errors().iter().map(|error| error.line()).collect::<Vec<usize>>(), vec![4]);
+        assert_eq!(*said.lock().unwrap(), vec!["Server: hello", "Server: hi Steve", "Server: bye"]);
+
+        let result = FunctionRunner::new().with_max_command_chain(1).run(&executor, &function, "Server".to_string(), None);
+        assert!(result.is_truncated() && result.executed() == 1);
+
+        let failing = parse(&"fail\n".repeat(10)).map_err(|error| anyhow::anyhow!("{}", error))?;
+        let result = FunctionRunner::new().with_max_command_chain(4).run(&executor, &failing, "Server".to_string(), None);
+        assert!(result.is_truncated());
+        assert_eq!((result.attempted(), result.errors().len()), (4, 4));
+
+        let injected = NbtCompound::from([("name".to_string(), NbtTag::String("$(name)".into()))]);
+        said.lock().unwrap().clear();
+        FunctionRunner::new().run(&executor, &parse("$say $(name)").map_err(|error| anyhow::anyhow!("{}", error))?, "Server".to_string(), Some(&injected));
+        assert_eq!(*said.lock().unwrap(), vec!["Server: $(name)"]);
+
+        assert_eq!(parse("say a\nkill @e").unwrap_err().line(), 2);
+        assert_eq!(parse("color red\ncolor reddish").unwrap_err().line(), 2);
+        assert_eq!(parse("say a\n/say b").unwrap_err().line(), 2);
+        assert!(parse("$say hi").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use crate::executor::command::{Command, Next};
+use crate::protocol::{BrigadierFlags, Node, NodeName};
+use minecraft_data_types::nums::VarInt;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Derives the declare commands node graph from an executable tree.
+///
+/// The root node is always at index 0. Flags are computed from the tree itself, so a node is only
+/// marked executable when the matching command has a function bound to it. When a namespace is
+/// given, every root literal also gets a `namespace:literal` redirect node. Commands with a
+/// redirect point at the node of their target path; a target path which doesn't exist in the tree
+/// is an error, since the client would otherwise be sent a graph that disagrees with execution.
+pub fn derive_nodes<T>(root: &Next<T>, namespace: Option<&str>) -> anyhow::Result<Vec<Node>> {
+    derive_usable_nodes(root, namespace, &|_| true)
+}
+
+/// Derives the node graph for a single sender, leaving out every node whose requirement the
+/// sender doesn't meet together with its children.
+///
+/// A redirect into a node the sender can't use is left out as well, like vanilla does.
+pub fn derive_nodes_for<T>(root: &Next<T>, namespace: Option<&str>, sender: &T) -> anyhow::Result<Vec<Node>> {
+    derive_usable_nodes(root, namespace, &|command| command.can_use(sender))
+}
+
+type Usable<'a, T> = dyn Fn(&Command<T>) -> bool + 'a;
+
+fn derive_usable_nodes<T>(root: &Next<T>, namespace: Option<&str>, usable: &Usable<T>) -> anyhow::Result<Vec<Node>> {
+    let mut known = HashMap::from([(Vec::new(), 0)]);
+    index_paths(root, &mut Vec::new(), &mut known, &|_| true);
+    let mut paths = HashMap::from([(Vec::new(), 0)]);
+    index_paths(root, &mut Vec::new(), &mut paths, usable);
+    let graph = Graph { known: &known, paths: &paths, usable };
+    let mut nodes = vec![None];
+    let mut children = graph.derive_children(root, &mut nodes)?;
+    if let (Some(namespace), Next::LiteralMap(map)) = (namespace, root) {
+        for ((literal, command), target) in usable_literals(map, usable).into_iter().zip(children.clone()) {
+            children.push(nodes.len());
+            nodes.push(Some(Node::new(
+                BrigadierFlags::new(true, false, command.is_executable(), true, false),
+                into_children(Vec::new()),
+                Some(into_index(target)),
+                Some(NodeName::from(format!("{}:{}", namespace, literal).as_str())),
+                None,
+                None,
+            )));
+        }
+    }
+    nodes[0] = Some(Node::new(
+        BrigadierFlags::new(false, false, false, false, false),
+        into_children(children),
+        None,
+        None,
+        None,
+        None,
+    ));
+    Ok(nodes
+        .into_iter()
+        .map(|node| node.expect("Every reserved node index should be filled."))
+        .collect())
+}
+
+fn usable_literals<'a, T>(map: &'a HashMap<String, Command<T>>, usable: &Usable<T>) -> Vec<(&'a String, &'a Command<T>)> {
+    sorted_literals(map).into_iter().filter(|(_, command)| usable(command)).collect()
+}
+
+pub(crate) fn sorted_literals<T>(map: &HashMap<String, Command<T>>) -> Vec<(&String, &Command<T>)> {
+    let mut literals = map.iter().collect::<Vec<(&String, &Command<T>)>>();
+    literals.sort_by(|item, other| item.0.cmp(other.0));
+    literals
+}
+
+fn into_index(index: usize) -> VarInt {
+    VarInt::try_from(index).expect("Node index should always fit into a var int size.")
+}
+
+fn into_children(children: Vec<usize>) -> (VarInt, Vec<VarInt>) {
+    (
+        VarInt::try_from(children.len()).expect("Children length should always fit into a var int size."),
+        children.into_iter().map(into_index).collect(),
+    )
+}
+
+/// Maps the path of every usable node to the index `derive_node` gives it, visiting in the same
+/// order.
+fn index_paths<T>(next: &Next<T>, path: &mut Vec<String>, paths: &mut HashMap<Vec<String>, usize>, usable: &Usable<T>) {
+    let children = match next {
+        Next::ArgumentParser(command @ Command::ArgParser(inner)) if usable(command) => vec![(inner.identifier(), command)],
+        Next::ArgumentParser(_) => Vec::new(),
+        Next::LiteralMap(map) => usable_literals(map, usable).into_iter().map(|(literal, command)| (literal.as_str(), command)).collect(),
+    };
+    for (segment, command) in children {
+        path.push(segment.to_string());
+        paths.insert(path.clone(), paths.len());
+        index_paths(command.next(), path, paths, usable);
+        path.pop();
+    }
+}
+
+/// Paths of every node in the tree and of the usable ones, which are the ones given an index.
+struct Graph<'a, T> {
+    known: &'a HashMap<Vec<String>, usize>,
+    paths: &'a HashMap<Vec<String>, usize>,
+    usable: &'a Usable<'a, T>,
+}
+
+impl<T> Graph<'_, T> {
+    fn derive_children(&self, next: &Next<T>, nodes: &mut Vec<Option<Node>>) -> anyhow::Result<Vec<usize>> {
+        match next {
+            Next::ArgumentParser(command) if (self.usable)(command) => Ok(vec![self.derive_node(None, command, nodes)?]),
+            Next::ArgumentParser(_) => Ok(Vec::new()),
+            Next::LiteralMap(map) => usable_literals(map, self.usable)
+                .into_iter()
+                .map(|(literal, command)| self.derive_node(Some(literal), command, nodes))
+                .collect(),
+        }
+    }
+
+    fn derive_node(&self, literal: Option<&str>, command: &Command<T>, nodes: &mut Vec<Option<Node>>) -> anyhow::Result<usize> {
+        let index = nodes.len();
+        nodes.push(None);
+        let children = into_children(self.derive_children(command.next(), nodes)?);
+        let redirect = match command.redirect() {
+            Some(redirect) => match self.paths.get(redirect.target()) {
+                Some(target) => Some(into_index(*target)),
+                None if self.known.contains_key(redirect.target()) => None,
+                None => anyhow::bail!("Unknown redirect target '{}'.", redirect.target().join(" ")),
+            },
+            None => None,
+        };
+        let node = match (literal, command) {
+            (Some(literal), Command::Natural(_)) => Node::new(
+                BrigadierFlags::new(true, false, command.is_executable(), redirect.is_some(), false),
+                children,
+                redirect,
+                Some(NodeName::from(literal)),
+                None,
+                None,
+            ),
+            (_, Command::ArgParser(inner)) => Node::new(
+                BrigadierFlags::new(false, true, command.is_executable(), redirect.is_some(), inner.suggestions_type().is_some()),
+                children,
+                redirect,
+                Some(NodeName::from(inner.identifier())),
+                Some(inner.parser().clone()),
+                inner.suggestions_type().cloned(),
+            ),
+            (None, Command::Natural(_)) => unreachable!("Natural commands are always bound through a literal map."),
+        };
+        nodes[index] = Some(node);
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand};
+    use crate::executor::context::Value;
+    use crate::executor::matching::LiteralMatching;
+    use crate::executor::redirect::Redirect;
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::protocol::{Parser, SuggestionsType};
+
+    #[test]
+    pub fn test_flags_follow_tree() -> anyhow::Result<()> {
+        let mut command = Command::Natural(NaturalCommand::<()>::default());
+        command.child(
+            "amount",
+            Command::ArgParser(
+                ArgParserCommand::executable(
+                    Box::new(|_| Ok(())),
+                    "amount".into(),
+                    Parser::Bool,
+                    Box::new(|context| Ok((Value::Generic(String::new()), context))),
+                )
+                .with_suggestions_type(SuggestionsType::AskServer),
+            ),
+        )?;
+        let mut executor = Executor::new();
+        executor.child("give", command)?;
+
+        let nodes = executor.nodes()?;
+        assert_eq!(nodes.len(), 3);
+        assert!(nodes[0].flags().is_root());
+        assert_eq!(*nodes[0].children(), vec![1.into()]);
+
+        let literal = nodes[1].flags();
+        assert!(literal.is_literal() && !literal.is_executable());
+        assert_eq!(*nodes[1].children(), vec![2.into()]);
+
+        let argument = nodes[2].flags();
+        assert!(argument.is_argument() && argument.is_executable() && argument.has_suggestions_type());
+        assert!(nodes[2].parser().is_some());
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_namespace_aliases() -> anyhow::Result<()> {
+        let mut executor = Executor::<()>::new().with_matching(LiteralMatching::exact().namespace("minecraft"));
+        executor.child("seed", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+
+        let nodes = executor.nodes()?;
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(*nodes[0].children(), vec![1.into(), 2.into()]);
+        let alias = nodes[2].flags();
+        assert!(alias.is_literal() && alias.is_executable() && alias.has_redirect());
+        assert_eq!(nodes[2].redirect_node(), Some(&1.into()));
+
+        executor.child("tp", Command::Natural(NaturalCommand::default().with_redirect(Redirect::to(vec!["teleport"]))))?;
+        assert_eq!(executor.nodes().unwrap_err().to_string(), "Unknown redirect target 'teleport'.");
+        executor.child("teleport", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+        assert!(executor.nodes()?.iter().any(|node| node.flags().has_redirect() && node.redirect_node().is_some()));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_nodes_for_sender() -> anyhow::Result<()> {
+        let mut executor = Executor::<u8>::new().with_matching(LiteralMatching::exact().namespace("minecraft"));
+        executor.child("seed", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+        executor.child(
+            "stop",
+            Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(()))).with_requirement(Box::new(|level| *level >= 4))),
+        )?;
+        executor.child("halt", Command::Natural(NaturalCommand::default().with_redirect(Redirect::to(vec!["stop"]))))?;
+
+        let operator = executor.nodes_for(&4)?;
+        assert_eq!(operator.len(), executor.nodes()?.len());
+        assert_eq!(operator[1].redirect_node(), Some(&3.into()));
+
+        let player = executor.nodes_for(&0)?;
+        assert_eq!(player.len(), 5);
+        assert_eq!(*player[0].children(), vec![1.into(), 2.into(), 3.into(), 4.into()]);
+        assert!(!player[1].flags().has_redirect() && player[1].redirect_node().is_none());
+        assert_eq!(player[4].redirect_node(), Some(&2.into()));
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use crate::executor::command::Command;
+use std::collections::HashMap;
+
+/// Policy used to resolve input tokens against literal children, for both execution and
+/// suggestions.
+///
+/// The default policy only accepts exact matches.
+#[derive(Debug, Clone, Default)]
+pub struct LiteralMatching {
+    case_insensitive: bool,
+    namespace: Option<String>,
+    abbreviations: bool,
+}
+
+impl LiteralMatching {
+    pub fn exact() -> Self {
+        Self::default()
+    }
+
+    /// Accepts literals regardless of the case they were typed in, e.g. `/Gamemode`.
+    ///
+    /// Only ASCII letters are folded, both for resolution and suggestions. When literals differ
+    /// only by case, an exact match wins and otherwise the smallest literal is picked.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Registers a `namespace:literal` alias for every root literal, e.g. `/minecraft:gamemode`.
+    pub fn namespace<S: Into<String>>(mut self, namespace: S) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Accepts any prefix of a literal as long as it only matches a single literal at that level.
+    pub fn abbreviations(mut self, abbreviations: bool) -> Self {
+        self.abbreviations = abbreviations;
+        self
+    }
+
+    pub fn namespace_prefix(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    fn equals(&self, literal: &str, token: &str) -> bool {
+        if self.case_insensitive {
+            literal.eq_ignore_ascii_case(token)
+        } else {
+            literal == token
+        }
+    }
+
+    fn starts_with(&self, literal: &str, partial: &str) -> bool {
+        literal.len() >= partial.len()
+            && literal.is_char_boundary(partial.len())
+            && self.equals(&literal[..partial.len()], partial)
+    }
+
+    fn strip_namespace<'a>(&self, token: &'a str) -> Option<&'a str> {
+        let namespace = self.namespace.as_ref()?;
+        let (prefix, literal) = token.split_once(':')?;
+        if self.equals(namespace, prefix) {
+            Some(literal)
+        } else {
+            None
+        }
+    }
+
+    /// Resolves a token against a literal map, returning the canonical literal and its command.
+    pub fn resolve<'a, T>(&self, map: &'a HashMap<String, Command<T>>, token: &str, root: bool) -> Option<(&'a String, &'a Command<T>)> {
+        let token = if root { self.strip_namespace(token).unwrap_or(token) } else { token };
+        if let Some(found) = map.get_key_value(token) {
+            return Some(found);
+        }
+        if self.case_insensitive {
+            // Literals differing only by case would otherwise resolve in hash order.
+            if let Some(found) = map.iter().filter(|(literal, _)| self.equals(literal, token)).min_by_key(|(literal, _)| *literal) {
+                return Some(found);
+            }
+        }
+        if self.abbreviations && !token.is_empty() {
+            let mut candidates = map.iter().filter(|(literal, _)| self.starts_with(literal, token));
+            if let (Some(found), None) = (candidates.next(), candidates.next()) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Lists the literals, including namespaced aliases at the root, which the partial token could
+    /// complete to.
+    pub fn suggest<T>(&self, map: &HashMap<String, Command<T>>, partial: &str, root: bool) -> Vec<String> {
+        let mut suggestions = map
+            .keys()
+            .filter(|literal| self.starts_with(literal, partial))
+            .cloned()
+            .collect::<Vec<String>>();
+        if let (true, Some(namespace)) = (root, &self.namespace) {
+            suggestions.extend(
+                map.keys()
+                    .map(|literal| format!("{}:{}", namespace, literal))
+                    .filter(|alias| self.starts_with(alias, partial)),
+            );
+        }
+        suggestions.sort();
+        suggestions
+    }
+
+    /// Lists up to three literals close to a mistyped token, closest first, for "did you mean"
+    /// hints. A literal is close when it is within one edit per three characters of the token.
+    pub fn similar<T>(&self, map: &HashMap<String, Command<T>>, token: &str, root: bool) -> Vec<String> {
+        let token = if root { self.strip_namespace(token).unwrap_or(token) } else { token };
+        let token = if self.case_insensitive { token.to_ascii_lowercase() } else { token.to_string() };
+        let threshold = (token.chars().count() / 3).max(1);
+        let mut candidates = map
+            .keys()
+            .filter_map(|literal| {
+                let distance = if self.case_insensitive {
+                    edit_distance(&literal.to_ascii_lowercase(), &token)
+                } else {
+                    edit_distance(literal, &token)
+                };
+                (distance <= threshold).then_some((distance, literal))
+            })
+            .collect::<Vec<(usize, &String)>>();
+        candidates.sort();
+        candidates.into_iter().take(3).map(|(_, literal)| literal.clone()).collect()
+    }
+}
+
+/// Number of insertions, deletions, substitutions and swaps of adjacent characters needed to turn
+/// one string into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1).min(row[j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{Command, NaturalCommand};
+    use crate::executor::matching::LiteralMatching;
+    use std::collections::HashMap;
+
+    fn literals() -> HashMap<String, Command<()>> {
+        let mut map = HashMap::new();
+        map.insert("gamemode".into(), Command::Natural(NaturalCommand::default()));
+        map.insert("gamerule".into(), Command::Natural(NaturalCommand::default()));
+        map.insert("give".into(), Command::Natural(NaturalCommand::default()));
+        map
+    }
+
+    fn resolve(matching: &LiteralMatching, token: &str) -> Option<String> {
+        matching.resolve(&literals(), token, true).map(|(literal, _)| literal.clone())
+    }
+
+    #[test]
+    pub fn test_resolution() {
+        let exact = LiteralMatching::exact();
+        assert_eq!(resolve(&exact, "give"), Some("give".into()));
+        assert_eq!(resolve(&exact, "Give"), None);
+        assert_eq!(resolve(&exact, "minecraft:give"), None);
+
+        let lenient = LiteralMatching::exact()
+            .case_insensitive(true)
+            .namespace("minecraft")
+            .abbreviations(true);
+        assert_eq!(resolve(&lenient, "Gamemode"), Some("gamemode".into()));
+        assert_eq!(resolve(&lenient, "Minecraft:gamemode"), Some("gamemode".into()));
+        assert_eq!(resolve(&lenient, "gamem"), Some("gamemode".into()));
+        assert_eq!(resolve(&lenient, "game"), None);
+        assert_eq!(resolve(&lenient, "gi"), Some("give".into()));
+
+        let mut colliding = literals();
+        colliding.insert("GIVE".into(), Command::Natural(NaturalCommand::default()));
+        colliding.insert("Give".into(), Command::Natural(NaturalCommand::default()));
+        let insensitive = LiteralMatching::exact().case_insensitive(true);
+        assert_eq!(insensitive.resolve(&colliding, "gIVE", true).map(|(literal, _)| literal.as_str()), Some("GIVE"));
+        assert_eq!(insensitive.resolve(&colliding, "Give", true).map(|(literal, _)| literal.as_str()), Some("Give"));
+    }
+
+    #[test]
+    pub fn test_suggestions() {
+        let matching = LiteralMatching::exact().namespace("minecraft");
+        assert_eq!(matching.suggest(&literals(), "ga", true), vec!["gamemode", "gamerule"]);
+        assert_eq!(matching.suggest(&literals(), "minecraft:gi", true), vec!["minecraft:give"]);
+        assert_eq!(matching.suggest(&literals(), "minecraft:gi", false), Vec::<String>::new());
+    }
+}
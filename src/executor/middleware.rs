@@ -0,0 +1,185 @@
+use crate::arguments::profile::{format_uuid, GameProfile};
+use crate::executor::context::CommandContext;
+use crate::executor::extensions::Extensions;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stable identity of a sender, used to key per-sender state such as cooldowns or audit events.
+pub trait SenderIdentity {
+    fn identity(&self) -> String;
+}
+
+impl SenderIdentity for String {
+    fn identity(&self) -> String {
+        self.clone()
+    }
+}
+
+impl SenderIdentity for u128 {
+    fn identity(&self) -> String {
+        format_uuid(*self)
+    }
+}
+
+impl SenderIdentity for GameProfile {
+    fn identity(&self) -> String {
+        format_uuid(self.uuid)
+    }
+}
+
+/// A single run of a command function, as seen by middleware.
+pub struct Invocation<'a> {
+    path: &'a [&'a str],
+    input: Arc<str>,
+    elapsed: Duration,
+    executed: bool,
+    state: Extensions,
+}
+
+impl<'a> Invocation<'a> {
+    pub(crate) fn new(path: &'a [&'a str], input: Arc<str>) -> Self {
+        Self { path, input, elapsed: Duration::ZERO, executed: false, state: Extensions::new() }
+    }
+
+    /// Literals and argument identifiers of the matched node, starting at the root or the target
+    /// of the last redirect.
+    pub fn path(&self) -> &[&'a str] {
+        self.path
+    }
+
+    /// The whole input the command was executed with.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// How long the command function took; zero until it ran.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Whether the command function ran, as opposed to being vetoed by middleware.
+    pub fn is_executed(&self) -> bool {
+        self.executed
+    }
+
+    /// State middleware carries from `before` to `after` for this invocation only, such as the
+    /// sender's name or parsed values.
+    pub fn state(&self) -> &Extensions {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut Extensions {
+        &mut self.state
+    }
+
+    pub(crate) fn finish(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+        self.executed = true;
+    }
+}
+
+/// Hooks around the command functions of an executor, for cross-cutting concerns such as
+/// permissions, cooldowns, logging or metrics which shouldn't be repeated inside every function.
+///
+/// `before` hooks run in the order the middleware was added and `after` hooks in reverse. An
+/// error from `before` vetoes the execution: the function and the remaining `before` hooks are
+/// skipped, but the `after` hooks of every middleware whose `before` ran still see the error.
+pub trait Middleware<T>: Send + Sync {
+    /// Called right before the command function runs, with the context it is about to receive.
+    fn before(&self, _invocation: &mut Invocation, _context: &CommandContext<T>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called with the result of the command, which can be replaced.
+    fn after(&self, _invocation: &Invocation, result: anyhow::Result<()>) -> anyhow::Result<()> {
+        result
+    }
+}
+
+pub type BeforeFunction<T> = Box<dyn Fn(&mut Invocation, &CommandContext<T>) -> anyhow::Result<()> + Send + Sync>;
+pub type AfterFunction = Box<dyn Fn(&Invocation, anyhow::Result<()>) -> anyhow::Result<()> + Send + Sync>;
+
+/// Middleware built from closures.
+pub struct Interceptor<T> {
+    before: Option<BeforeFunction<T>>,
+    after: Option<AfterFunction>,
+}
+
+impl<T> Default for Interceptor<T> {
+    fn default() -> Self {
+        Self { before: None, after: None }
+    }
+}
+
+impl<T> Interceptor<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn before(mut self, before: BeforeFunction<T>) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    pub fn after(mut self, after: AfterFunction) -> Self {
+        self.after = Some(after);
+        self
+    }
+}
+
+impl<T> Middleware<T> for Interceptor<T> {
+    fn before(&self, invocation: &mut Invocation, context: &CommandContext<T>) -> anyhow::Result<()> {
+        match &self.before {
+            Some(before) => before(invocation, context),
+            None => Ok(()),
+        }
+    }
+
+    fn after(&self, invocation: &Invocation, result: anyhow::Result<()>) -> anyhow::Result<()> {
+        match &self.after {
+            Some(after) => after(invocation, result),
+            None => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{Command, NaturalCommand};
+    use crate::executor::context::CommandContext;
+    use crate::executor::middleware::Interceptor;
+    use crate::executor::{CommandChildContainer, Executor};
+    use std::sync::{Arc, Mutex};
+
+    struct Sender(String);
+
+    #[test]
+    pub fn test_interceptors() -> anyhow::Result<()> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let audit = log.clone();
+        let permissions = Interceptor::<String>::new().before(Box::new(|_, context| {
+            if context.sender() == "Banned" {
+                anyhow::bail!("You are banned");
+            }
+            Ok(())
+        }));
+        let logging = Interceptor::new()
+            .before(Box::new(|invocation, context: &CommandContext<String>| {
+                invocation.state_mut().insert(Sender(context.sender().clone()));
+                Ok(())
+            }))
+            .after(Box::new(move |invocation, result| {
+                let sender = &invocation.state().get::<Sender>().unwrap().0;
+                audit.lock().unwrap().push(format!("{} ran /{} at {}: {}", sender, invocation.input(), invocation.path().join(" "), result.is_ok()));
+                result.map_err(|error| anyhow::anyhow!("Failed: {}", error))
+            }));
+        let mut executor = Executor::new().with_middleware(logging).with_middleware(permissions);
+        executor.child("stop", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+
+        executor.execute_context(CommandContext::create("Admin", vec!["stop", "now"]))?.unwrap()?;
+        let vetoed = executor.execute_context(CommandContext::create("Banned", vec!["stop"]))?.unwrap();
+        assert_eq!(vetoed.unwrap_err().to_string(), "Failed: You are banned");
+        assert_eq!(*log.lock().unwrap(), vec!["Admin ran /stop now at stop: true", "Banned ran /stop at stop: false"]);
+        Ok(())
+    }
+}
@@ -1,10 +1,28 @@
-use crate::executor::command::{Command, Next};
+use crate::executor::command::{Command, Dispatch, Either, Next};
 use crate::executor::context::CommandContext;
+use crate::executor::extensions::Extensions;
+use crate::executor::matching::LiteralMatching;
+use crate::executor::middleware::Middleware;
+use std::sync::Arc;
 
 pub mod context;
+pub mod extensions;
+pub mod function;
 pub mod macros;
+pub mod audit;
 pub mod command;
+pub mod concurrent;
 pub mod builder;
+pub mod graph;
+pub mod matching;
+pub mod middleware;
+pub mod cooldown;
+pub mod parse;
+pub mod redirect;
+pub mod shared;
+pub mod source;
+pub mod trace;
+pub mod unknown;
 
 pub trait CommandChildContainer<T> {
     fn child<S: Into<String>>(&mut self, identifier: S, command: command::Command<T>) -> anyhow::Result<()>;
@@ -12,14 +30,181 @@ pub trait CommandChildContainer<T> {
     fn execute_context(&self, context: context::CommandContext<T>) -> anyhow::Result<Option<anyhow::Result<()>>>;
 }
 
+/// Root of a command tree.
+///
+/// Executing only needs `&self` and every function stored in the tree is `Send + Sync`, so an
+/// executor is `Send + Sync` for any sender type and can be shared behind an `Arc` by any number
+/// of threads executing at the same time. Senders are moved into the context of a single
+/// execution and never shared between executions. Changing the tree needs `&mut self`; use a
+/// `SharedExecutor` to register commands while other threads execute.
 #[derive(Default)]
 pub struct Executor<T> {
     next: command::Next<T>,
+    matching: LiteralMatching,
+    extensions: Arc<Extensions>,
+    middleware: Vec<Arc<dyn Middleware<T>>>,
 }
 
+const _: () = {
+    fn assert_send_sync<S: Send + Sync>() {}
+
+    #[allow(dead_code)]
+    fn executor_is_send_sync<T>() {
+        assert_send_sync::<Executor<T>>();
+        assert_send_sync::<shared::SharedExecutor<T>>();
+    }
+};
+
 impl<T> Executor<T> {
     pub fn new() -> Self {
-        Self { next: Next::default() }
+        Self { next: Next::default(), matching: LiteralMatching::default(), extensions: Arc::default(), middleware: Vec::new() }
+    }
+
+    /// Adds middleware around every command function; see `Middleware` for the order hooks run in.
+    pub fn with_middleware<M: Middleware<T> + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    fn dispatch(&self) -> Dispatch<'_, T> {
+        Dispatch::new(&self.matching).with_root(&self.next).with_middleware(&self.middleware)
+    }
+
+    /// Adds state every handler run by this executor can read through `CommandContext::state`.
+    pub fn with_state<S: Send + Sync + 'static>(mut self, state: S) -> Self {
+        Arc::make_mut(&mut self.extensions).insert(state);
+        self
+    }
+
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn with_matching(mut self, matching: LiteralMatching) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    pub fn matching(&self) -> &LiteralMatching {
+        &self.matching
+    }
+
+    /// Derives the node graph sent to clients; fails if a redirect points at a missing node.
+    pub fn nodes(&self) -> anyhow::Result<Vec<crate::protocol::Node>> {
+        graph::derive_nodes(&self.next, self.matching.namespace_prefix())
+    }
+
+    /// Derives the node graph for a single sender, without the commands it can't use.
+    pub fn nodes_for(&self, sender: &T) -> anyhow::Result<Vec<crate::protocol::Node>> {
+        graph::derive_nodes_for(&self.next, self.matching.namespace_prefix(), sender)
+    }
+
+    pub fn execute_dispatch<'a>(&'a self, mut context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        context.attach_extensions(&self.extensions);
+        match Command::__next(&self.next, context, dispatch, true)? {
+            Either::Left(_) => Ok(None),
+            Either::Right(pushed) => Ok(pushed),
+        }
+    }
+
+    /// Executes the context and returns how many command functions ran successfully, which can be
+    /// more than one when a forked redirect runs the rest of the input for several senders.
+    pub fn execute_count(&self, context: CommandContext<T>) -> anyhow::Result<usize> {
+        self.execute_counted(context, &mut self.dispatch())
+    }
+
+    fn execute_counted<'a>(&'a self, context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>) -> anyhow::Result<usize> {
+        let input = context.args().input().clone();
+        match self.execute_dispatch(context, dispatch)? {
+            Some(result) => result.map(|_| dispatch.successes()),
+            None => Err(unknown::UnknownCommandError::new(dispatch, &input).into()),
+        }
+    }
+
+
+    /// Executes the context, failing with an `UnknownCommandError` suggesting similar literals when
+    /// the input doesn't reach a command function.
+    pub fn run(&self, context: CommandContext<T>) -> anyhow::Result<()> {
+        self.execute_count(context).map(|_| ())
+    }
+
+    /// Splits raw input such as `gamemode creative` and runs it for the sender.
+    pub fn run_input(&self, sender: T, input: &str) -> anyhow::Result<()> {
+        self.run(CommandContext::from_input(sender, input))
+    }
+
+    /// Matches and parses the input of the context without running any command function.
+    ///
+    /// The sender of the context is only handed to the parsers; the results can be cached and
+    /// executed for any sender with `execute`, unless a parser resolved values against the sender
+    /// such as `source::resolved_vec3_parser`.
+    pub fn parse(&self, mut context: CommandContext<T>) -> anyhow::Result<parse::ParseResults> {
+        context.attach_extensions(&self.extensions);
+        parse::parse(&self.next, &self.matching, context)
+    }
+
+    /// Executes parse results for the sender, without re-running the parsers.
+    pub fn execute(&self, results: &parse::ParseResults, sender: T) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        let mut context = CommandContext::from_args(sender, results.remaining().clone());
+        context.attach_extensions(&self.extensions);
+        parse::execute(&self.next, results, context, &mut self.dispatch())
+    }
+
+    /// Executes parse results for the sender like `execute`, attempting at most `limit` command
+    /// functions including forked ones; returns how many ran successfully, how many were attempted
+    /// and whether the limit stopped any.
+    pub(crate) fn execute_limited(&self, results: &parse::ParseResults, sender: T, limit: usize) -> (anyhow::Result<usize>, usize, bool) {
+        let mut dispatch = self.dispatch().with_limit(limit);
+        let mut context = CommandContext::from_args(sender, results.remaining().clone());
+        context.attach_extensions(&self.extensions);
+        let result = match parse::execute(&self.next, results, context, &mut dispatch) {
+            Ok(Some(result)) => result.map(|_| dispatch.successes()),
+            Ok(None) => Err(unknown::UnknownCommandError::new(&dispatch, results.input().input()).into()),
+            Err(error) => Err(error),
+        };
+        (result, dispatch.attempts(), dispatch.is_limited())
+    }
+
+    /// Executes the context while recording every step of the walk through the tree.
+    pub fn execute_traced(&self, context: CommandContext<T>) -> (anyhow::Result<Option<anyhow::Result<()>>>, trace::ExecutionTrace) {
+        let mut trace = trace::ExecutionTrace::default();
+        let result = self.execute_dispatch(context, &mut Dispatch::traced(&self.matching, &mut trace).with_root(&self.next).with_middleware(&self.middleware));
+        (result, trace)
+    }
+
+    /// Renders the command tree as an indented ASCII tree.
+    pub fn render_tree(&self) -> String {
+        trace::render_tree(&self.next)
+    }
+
+    /// Exports the derived node graph in the format of the vanilla `commands.json` report.
+    pub fn to_commands_json(&self) -> anyhow::Result<serde_json::Value> {
+        crate::export::to_commands_json(&self.nodes()?)
+    }
+
+    /// Exports the derived node graph as a Graphviz DOT digraph.
+    pub fn to_dot(&self) -> anyhow::Result<String> {
+        crate::export::to_dot(&self.nodes()?)
+    }
+
+    /// Suggests completions for the last argument of the context, which is treated as partial.
+    pub fn suggest(&self, mut context: CommandContext<T>) -> anyhow::Result<Vec<String>> {
+        context.attach_extensions(&self.extensions);
+        Command::__suggest(&self.next, context, &self.matching, true)
+    }
+
+    pub fn contains(&self, identifier: &str) -> bool {
+        match &self.next {
+            Next::LiteralMap(map) => map.contains_key(identifier),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn remove(&mut self, identifier: &str) -> Option<Command<T>> {
+        match &mut self.next {
+            Next::LiteralMap(map) => map.remove(identifier),
+            _ => unreachable!(),
+        }
     }
 }
 
@@ -39,21 +224,7 @@ impl<T> CommandChildContainer<T> for Executor<T> {
         }
     }
 
-    fn execute_context(&self, mut context: CommandContext<T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
-        match &self.next {
-            Next::LiteralMap(map) => {
-                Ok(if let Some(arg) = context.args().first() {
-                    if let Some(command) = map.get(arg) {
-                        context.trim_top();
-                        command.execute_context(context)?
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                })
-            }
-            _ => unreachable!(),
-        }
+    fn execute_context(&self, context: CommandContext<T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        self.execute_dispatch(context, &mut self.dispatch())
     }
 }
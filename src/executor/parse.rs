@@ -0,0 +1,176 @@
+use crate::executor::command::{Command, Dispatch, Next};
+use crate::executor::context::{ArgValues, Args, CommandContext};
+use crate::executor::matching::LiteralMatching;
+use crate::executor::redirect;
+
+/// Part of a parse up to the next redirect, or the end of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedStage {
+    path: Vec<String>,
+    values: ArgValues,
+}
+
+impl ParsedStage {
+    /// Literals and argument identifiers from the root to the last node of the stage.
+    pub fn path(&self) -> &Vec<String> {
+        &self.path
+    }
+
+    pub fn values(&self) -> &ArgValues {
+        &self.values
+    }
+}
+
+/// Input which has been matched against the tree and parsed once, so it can be executed again
+/// for any number of senders without re-running the parsers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseResults {
+    input: Args,
+    stages: Vec<ParsedStage>,
+    remaining: Args,
+    executable: bool,
+}
+
+impl ParseResults {
+    pub fn input(&self) -> &Args {
+        &self.input
+    }
+
+    /// Stages of the parse; every stage but the last ends at a redirect.
+    pub fn stages(&self) -> &Vec<ParsedStage> {
+        &self.stages
+    }
+
+    /// Tokens left over once no child matched, passed to the command function.
+    pub fn remaining(&self) -> &Args {
+        &self.remaining
+    }
+
+    /// Whether the input reached a node with a command function bound to it.
+    pub fn is_executable(&self) -> bool {
+        self.executable
+    }
+}
+
+/// Walks the tree like an execution would, running every parser but no command function.
+pub(crate) fn parse<T>(root: &Next<T>, matching: &LiteralMatching, mut context: CommandContext<T>) -> anyhow::Result<ParseResults> {
+    let input = context.args().clone();
+    let mut stages = Vec::new();
+    let mut path = Vec::new();
+    let mut next = root;
+    let mut at_root = true;
+    let mut command = None;
+    loop {
+        let (segment, child) = match next {
+            Next::ArgumentParser(child @ Command::ArgParser(parser)) if !context.args().is_empty() && child.can_use(context.sender()) => {
+                context = parser.parse(context)?;
+                (parser.identifier().to_string(), child)
+            }
+            Next::LiteralMap(map) => match context.args().first().and_then(|token| matching.resolve(map, token, at_root)).filter(|(_, child)| child.can_use(context.sender())) {
+                Some((literal, child)) => {
+                    context.trim_top();
+                    (literal.clone(), child)
+                }
+                None => break,
+            },
+            Next::ArgumentParser(_) => break,
+        };
+        path.push(segment);
+        command = Some(child);
+        match child.redirect() {
+            Some(target) if !context.args().is_empty() => {
+                stages.push(ParsedStage { path: std::mem::replace(&mut path, target.target().clone()), values: context.take_values() });
+                next = redirect::resolve_target(root, target.target())
+                    .ok_or_else(|| anyhow::anyhow!("Unknown redirect target '{}'.", target.target().join(" ")))?;
+                at_root = target.target().is_empty();
+                command = None;
+            }
+            _ => {
+                next = child.next();
+                at_root = false;
+            }
+        }
+    }
+    let executable = command.is_some_and(|command| command.is_executable());
+    stages.push(ParsedStage { path, values: context.take_values() });
+    Ok(ParseResults { input, stages, remaining: context.args().clone(), executable })
+}
+
+fn replay<'a, T>(root: &'a Next<T>, results: &'a ParseResults, stage: usize, mut context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+    let parsed = &results.stages[stage];
+    if (stage + 1 == results.stages.len() && !results.executable) || !redirect::is_usable(root, &parsed.path, context.sender()) {
+        return Ok(None);
+    }
+    let command = redirect::resolve_command(root, &parsed.path)
+        .ok_or_else(|| anyhow::anyhow!("The command tree changed since '{}' was parsed.", results.input.rest()))?;
+    for value in parsed.values.borrow_inner() {
+        context.value_arg(value.clone());
+    }
+    if stage + 1 == results.stages.len() {
+        let outer = dispatch.enter_path(&parsed.path);
+        let result = command.run(context, dispatch);
+        dispatch.restore_path(outer);
+        return Ok(result);
+    }
+    let target = command
+        .redirect()
+        .ok_or_else(|| anyhow::anyhow!("The command tree changed since '{}' was parsed.", results.input.rest()))?;
+    redirect::fan_out(target, context, dispatch, |context, dispatch| replay(root, results, stage + 1, context, dispatch))
+}
+
+/// Executes parse results, applying redirect modifiers for the new sender.
+pub(crate) fn execute<'a, T>(root: &'a Next<T>, results: &'a ParseResults, context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+    replay(root, results, 0, context, dispatch)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand};
+    use crate::executor::context::{CommandContext, Value};
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::protocol::{Parser, StringDescription};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    pub fn test_parse_once_execute_many() -> anyhow::Result<()> {
+        let parsed = Arc::new(AtomicUsize::new(0));
+        let given = Arc::new(Mutex::new(Vec::new()));
+        let (parse_count, log) = (parsed.clone(), given.clone());
+
+        let mut give = Command::Natural(NaturalCommand::default());
+        give.child(
+            "item",
+            Command::ArgParser(ArgParserCommand::executable(
+                Box::new(move |context: CommandContext<String>| {
+                    let item = crate::unwrap_value!(context.value("item").unwrap() => String);
+                    log.lock().unwrap().push(format!("{} {}", context.sender(), item));
+                    Ok(())
+                }),
+                "item".into(),
+                Parser::String { info: StringDescription::SingleWord },
+                Box::new(move |mut context| {
+                    parse_count.fetch_add(1, Ordering::SeqCst);
+                    let item = context.args()[0].to_string();
+                    context.trim_top();
+                    Ok((Value::String(item), context))
+                }),
+            )),
+        )?;
+        let mut executor = Executor::new();
+        executor.child("give", give)?;
+
+        let results = executor.parse(CommandContext::create("Server", vec!["give", "diamond"]))?;
+        assert!(results.is_executable());
+        assert_eq!(*results.stages()[0].path(), vec!["give", "item"]);
+        executor.execute(&results, "Alex".to_string())?.unwrap()?;
+        executor.execute(&results, "Steve".to_string())?.unwrap()?;
+        assert_eq!(parsed.load(Ordering::SeqCst), 1);
+        assert_eq!(*given.lock().unwrap(), vec!["Alex diamond", "Steve diamond"]);
+
+        assert!(!executor.parse(CommandContext::create("Server", vec!["give"]))?.is_executable());
+        assert!(!executor.parse(CommandContext::create("Server", vec!["take", "dirt"]))?.is_executable());
+        assert!(executor.execute(&executor.parse(CommandContext::create("Server", vec!["take"]))?, "Alex".into())?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::executor::command::{Command, Dispatch, Next};
+use crate::executor::context::CommandContext;
+use crate::executor::trace::TraceEvent;
+
+/// Derives the senders a redirect continues with from the context reaching it.
+pub type RedirectModifier<T> = Box<dyn Fn(&CommandContext<T>) -> Vec<T> + Send + Sync>;
+
+/// Continues parsing the remaining input at another node of the tree, like `/execute as`.
+///
+/// The target is a path of literals and argument identifiers from the executor root; an empty
+/// path redirects to the root itself. Without a modifier the context continues unchanged,
+/// otherwise the rest of the input runs once per sender returned by the modifier.
+pub struct Redirect<T> {
+    target: Vec<String>,
+    modifier: Option<RedirectModifier<T>>,
+    fork: bool,
+}
+
+impl<T> Redirect<T> {
+    pub fn root() -> Self {
+        Self { target: Vec::new(), modifier: None, fork: false }
+    }
+
+    pub fn to<S: Into<String>>(target: Vec<S>) -> Self {
+        Self { target: target.into_iter().map(Into::into).collect(), modifier: None, fork: false }
+    }
+
+    /// Runs the rest of the input for every sender; the first failure stops the redirect.
+    pub fn with_modifier(mut self, modifier: RedirectModifier<T>) -> Self {
+        self.modifier = Some(modifier);
+        self.fork = false;
+        self
+    }
+
+    /// Runs the rest of the input for every sender, ignoring the branches that fail.
+    pub fn forked(mut self, modifier: RedirectModifier<T>) -> Self {
+        self.modifier = Some(modifier);
+        self.fork = true;
+        self
+    }
+
+    pub fn target(&self) -> &Vec<String> {
+        &self.target
+    }
+
+    pub fn modifier(&self) -> Option<&RedirectModifier<T>> {
+        self.modifier.as_ref()
+    }
+
+    pub fn is_fork(&self) -> bool {
+        self.fork
+    }
+}
+
+fn child<'a, T>(next: &'a Next<T>, segment: &str) -> Option<&'a Command<T>> {
+    match next {
+        Next::LiteralMap(map) => map.get(segment),
+        Next::ArgumentParser(command @ Command::ArgParser(inner)) if inner.identifier() == segment => Some(command),
+        Next::ArgumentParser(_) => None,
+    }
+}
+
+/// Finds the children of the node a redirect path points at.
+pub(crate) fn resolve_target<'a, T>(root: &'a Next<T>, target: &[String]) -> Option<&'a Next<T>> {
+    let mut next = root;
+    for segment in target {
+        next = child(next, segment)?.next();
+    }
+    Some(next)
+}
+
+/// Finds the command at the end of a non-empty path from the root.
+pub(crate) fn resolve_command<'a, T>(root: &'a Next<T>, path: &[String]) -> Option<&'a Command<T>> {
+    let (last, parent) = path.split_last()?;
+    child(resolve_target(root, parent)?, last)
+}
+
+/// Whether the sender can use every node of a path from the root, including the nodes a redirect
+/// target path passes through.
+pub(crate) fn is_usable<T>(root: &Next<T>, path: &[String], sender: &T) -> bool {
+    let mut next = root;
+    for segment in path {
+        match child(next, segment) {
+            Some(command) if !command.can_use(sender) => return false,
+            Some(command) => next = command.next(),
+            None => break,
+        }
+    }
+    true
+}
+
+/// Runs a branch for every sender the redirect continues with and aggregates the results.
+///
+/// Forked branches never fail the redirect; otherwise the first error is returned and the
+/// redirect succeeds if any branch did, or if there were no senders to run a branch for.
+pub(crate) fn fan_out<'a, T, F>(redirect: &Redirect<T>, context: CommandContext<T>, dispatch: &mut Dispatch<'a, T>, mut branch: F) -> anyhow::Result<Option<anyhow::Result<()>>>
+where
+    F: FnMut(CommandContext<T>, &mut Dispatch<'a, T>) -> anyhow::Result<Option<anyhow::Result<()>>>,
+{
+    let modifier = match redirect.modifier() {
+        Some(modifier) => modifier,
+        None => {
+            dispatch.record(|| TraceEvent::Redirected { target: redirect.target().clone(), senders: 1 });
+            return branch(context, dispatch);
+        }
+    };
+    let senders = modifier(&context);
+    dispatch.record(|| TraceEvent::Redirected { target: redirect.target().clone(), senders: senders.len() });
+    let mut result = if redirect.is_fork() || senders.is_empty() { Some(Ok(())) } else { None };
+    for sender in senders {
+        if dispatch.refuse() {
+            break;
+        }
+        let outcome = branch(context.redirected(sender), dispatch);
+        if redirect.is_fork() {
+            continue;
+        }
+        match outcome? {
+            Some(Err(error)) => return Ok(Some(Err(error))),
+            Some(Ok(())) => result = Some(Ok(())),
+            None => {}
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand};
+    use crate::executor::context::{CommandContext, Value};
+    use crate::executor::redirect::Redirect;
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::protocol::{Parser, StringDescription};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    pub fn test_forked_redirect() -> anyhow::Result<()> {
+        let said = Arc::new(Mutex::new(Vec::new()));
+        let mut executor = Executor::<String>::new();
+
+        let log = said.clone();
+        executor.child(
+            "say",
+            Command::Natural(NaturalCommand::executable(Box::new(move |context: CommandContext<String>| {
+                if context.sender() == "Creeper" {
+                    anyhow::bail!("Creepers can't talk");
+                }
+                log.lock().unwrap().push(context.sender().clone());
+                Ok(())
+            }))),
+        )?;
+
+        let mut root_execute = Command::Natural(NaturalCommand::default());
+        root_execute.child("run", Command::Natural(NaturalCommand::default().with_redirect(Redirect::root())))?;
+
+        let mut targets = Command::Natural(NaturalCommand::default());
+        targets.child(
+            "targets",
+            Command::ArgParser(
+                ArgParserCommand::non_executable(
+                    "targets".into(),
+                    Parser::String { info: StringDescription::SingleWord },
+                    Box::new(|mut context| {
+                        let names = context.args()[0].to_string();
+                        context.trim_top();
+                        Ok((Value::String(names), context))
+                    }),
+                )
+                .with_redirect(Redirect::to(vec!["execute"]).forked(Box::new(|context| match context.value("targets") {
+                    Some(Value::String(names)) => names.split(',').map(String::from).collect(),
+                    _ => Vec::new(),
+                }))),
+            ),
+        )?;
+        root_execute.child("as", targets)?;
+        root_execute.child("nobody", Command::Natural(NaturalCommand::default().with_redirect(Redirect::to(vec!["execute"]).with_modifier(Box::new(|_| Vec::new())))))?;
+        executor.child("execute", root_execute)?;
+
+        let input = vec!["execute", "as", "Alex,Creeper,Steve", "run", "say"];
+        assert_eq!(executor.execute_count(CommandContext::create("Server", input))?, 2);
+        assert_eq!(*said.lock().unwrap(), vec!["Alex", "Steve"]);
+
+        assert_eq!(executor.execute_count(CommandContext::create("Server", vec!["execute", "nobody", "run", "say"]))?, 0);
+
+        let nested = vec!["execute", "as", "Alex,Steve", "as", "Bob", "run", "say"];
+        assert_eq!(executor.execute_count(CommandContext::create("Server", nested))?, 2);
+
+        let results = executor.parse(CommandContext::create("Server", vec!["execute", "as", "Alex,Creeper,Steve", "run", "say"]))?;
+        let (_, attempts, limited) = executor.execute_limited(&results, "Server".into(), 1);
+        assert!(attempts == 1 && limited);
+
+        let nodes = executor.nodes()?;
+        let redirects = nodes.iter().filter(|node| node.flags().has_redirect()).count();
+        assert_eq!(redirects, 3);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_redirect_requirements() -> anyhow::Result<()> {
+        let mut stop = Command::Natural(NaturalCommand::default().with_requirement(Box::new(|level: &u8| *level >= 4)));
+        stop.child("now", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+        let mut executor = Executor::<u8>::new();
+        executor.child("stop", stop)?;
+        executor.child("halt", Command::Natural(NaturalCommand::default().with_redirect(Redirect::to(vec!["stop"]))))?;
+
+        assert_eq!(executor.execute_count(CommandContext::create(4, vec!["halt", "now"]))?, 1);
+        assert!(executor.execute_count(CommandContext::create(0, vec!["halt", "now"])).is_err());
+
+        let results = executor.parse(CommandContext::create(4, vec!["halt", "now"]))?;
+        assert!(executor.execute(&results, 4)?.is_some());
+        assert!(executor.execute(&results, 0)?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+use crate::executor::command::Command;
+use crate::executor::context::CommandContext;
+use crate::executor::{CommandChildContainer, Executor};
+use crate::protocol::Node;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+pub type ChangeListener = Box<dyn Fn(&ExecutorChange, &[Node]) + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutorChange {
+    Registered(String),
+    Unregistered(String),
+}
+
+/// Proof of a runtime registration, required to unregister the command again.
+///
+/// Tokens are unique per registration, so a stale token cannot remove a command that was
+/// registered again under the same literal.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct RegistrationToken {
+    identifier: String,
+    id: u64,
+}
+
+impl RegistrationToken {
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+}
+
+struct Registrations<T> {
+    executor: Executor<T>,
+    tokens: HashMap<String, u64>,
+}
+
+struct SharedInner<T> {
+    registrations: RwLock<Registrations<T>>,
+    listeners: RwLock<Vec<ChangeListener>>,
+    /// Held from the end of a change until its listeners were notified, so notifications are
+    /// delivered in the order the changes were made.
+    notifying: Mutex<()>,
+    next_id: AtomicU64,
+}
+
+thread_local! {
+    /// Shared executors the current thread is executing a command of, by address.
+    static EXECUTING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks the current thread as executing through a shared executor until dropped.
+struct Executing(usize);
+
+impl Executing {
+    fn enter(address: usize) -> Self {
+        EXECUTING.with(|executing| executing.borrow_mut().push(address));
+        Self(address)
+    }
+
+    fn is_active(address: usize) -> bool {
+        EXECUTING.with(|executing| executing.borrow().contains(&address))
+    }
+}
+
+impl Drop for Executing {
+    fn drop(&mut self) {
+        EXECUTING.with(|executing| {
+            let mut executing = executing.borrow_mut();
+            if let Some(index) = executing.iter().rposition(|address| *address == self.0) {
+                executing.remove(index);
+            }
+        });
+    }
+}
+
+/// Cloneable handle to an executor which can have root commands registered and unregistered at
+/// runtime while other threads execute commands through it.
+///
+/// Commands run while holding a read lock on the tree, so registering waits for commands which
+/// are already running. Registering or unregistering from inside a command of the same shared
+/// executor would wait for itself; it fails with an error instead, so e.g. a `/reload` command has
+/// to hand the change to another thread or task. Listeners are called one change at a time, in the
+/// order of the changes, and must not change the shared executor themselves.
+pub struct SharedExecutor<T> {
+    inner: Arc<SharedInner<T>>,
+}
+
+impl<T> Clone for SharedExecutor<T> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<T> Default for SharedExecutor<T> {
+    fn default() -> Self {
+        Self::new(Executor::new())
+    }
+}
+
+impl<T> From<Executor<T>> for SharedExecutor<T> {
+    fn from(executor: Executor<T>) -> Self {
+        Self::new(executor)
+    }
+}
+
+impl<T> SharedExecutor<T> {
+    /// Wraps an executor; commands it already contains are not tied to a token and stay registered.
+    pub fn new(executor: Executor<T>) -> Self {
+        Self {
+            inner: Arc::new(SharedInner {
+                registrations: RwLock::new(Registrations { executor, tokens: HashMap::new() }),
+                listeners: RwLock::new(Vec::new()),
+                notifying: Mutex::new(()),
+                next_id: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    fn read(&self) -> anyhow::Result<RwLockReadGuard<'_, Registrations<T>>> {
+        self.inner.registrations.read().map_err(|_| anyhow::anyhow!("Shared executor lock was poisoned."))
+    }
+
+    fn address(&self) -> usize {
+        Arc::as_ptr(&self.inner) as *const () as usize
+    }
+
+    fn write(&self) -> anyhow::Result<RwLockWriteGuard<'_, Registrations<T>>> {
+        match self.inner.registrations.try_write() {
+            Ok(registrations) => Ok(registrations),
+            Err(TryLockError::WouldBlock) if Executing::is_active(self.address()) => {
+                anyhow::bail!("Cannot change a shared executor from a command it is executing.")
+            }
+            Err(TryLockError::WouldBlock) => self.inner.registrations.write().map_err(|_| anyhow::anyhow!("Shared executor lock was poisoned.")),
+            Err(TryLockError::Poisoned(_)) => anyhow::bail!("Shared executor lock was poisoned."),
+        }
+    }
+
+    /// Reserves the next notification slot; taken while the change is still locked in.
+    fn order(&self) -> anyhow::Result<MutexGuard<'_, ()>> {
+        self.inner.notifying.lock().map_err(|_| anyhow::anyhow!("Shared executor notification lock was poisoned."))
+    }
+
+    fn has_listeners(&self) -> anyhow::Result<bool> {
+        Ok(!self
+            .inner
+            .listeners
+            .read()
+            .map_err(|_| anyhow::anyhow!("Shared executor listener lock was poisoned."))?
+            .is_empty())
+    }
+
+    /// Registers a listener which is notified with the rebuilt node graph after every change.
+    pub fn on_change(&self, listener: ChangeListener) -> anyhow::Result<()> {
+        self.inner
+            .listeners
+            .write()
+            .map_err(|_| anyhow::anyhow!("Shared executor listener lock was poisoned."))?
+            .push(listener);
+        Ok(())
+    }
+
+    /// Registers a root command; fails without changing anything if the command or a redirect
+    /// into it would leave a redirect pointing at a missing node.
+    pub fn register<S: Into<String>>(&self, identifier: S, command: Command<T>) -> anyhow::Result<RegistrationToken> {
+        let identifier = identifier.into();
+        let (token, nodes, order) = {
+            let mut registrations = self.write()?;
+            if registrations.executor.contains(&identifier) {
+                anyhow::bail!("A command is already registered as {}.", identifier);
+            }
+            registrations.executor.child(identifier.clone(), command)?;
+            let nodes = match registrations.executor.nodes() {
+                Ok(nodes) => nodes,
+                Err(error) => {
+                    registrations.executor.remove(&identifier);
+                    return Err(error);
+                }
+            };
+            let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+            registrations.tokens.insert(identifier.clone(), id);
+            let nodes = if self.has_listeners()? { Some(nodes) } else { None };
+            (RegistrationToken { identifier: identifier.clone(), id }, nodes, self.order()?)
+        };
+        self.notify(ExecutorChange::Registered(identifier), nodes, order)?;
+        Ok(token)
+    }
+
+    pub fn unregister(&self, token: RegistrationToken) -> anyhow::Result<Command<T>> {
+        let (command, nodes, order) = {
+            let mut registrations = self.write()?;
+            if registrations.tokens.get(&token.identifier) != Some(&token.id) {
+                anyhow::bail!("Registration token for {} is no longer valid.", token.identifier);
+            }
+            registrations.tokens.remove(&token.identifier);
+            let command = registrations
+                .executor
+                .remove(&token.identifier)
+                .expect("Registered commands should be present in the executor.");
+            let nodes = match registrations.executor.nodes() {
+                Ok(nodes) if self.has_listeners()? => Some(nodes),
+                Ok(_) => None,
+                Err(error) => {
+                    registrations.executor.child(token.identifier.clone(), command)?;
+                    registrations.tokens.insert(token.identifier.clone(), token.id);
+                    return Err(error);
+                }
+            };
+            (command, nodes, self.order()?)
+        };
+        self.notify(ExecutorChange::Unregistered(token.identifier), nodes, order)?;
+        Ok(command)
+    }
+
+    /// Notifies the listeners with the graph derived while the change was still locked in.
+    ///
+    /// The notification slot was taken before the registrations were unlocked, so the listeners
+    /// of a later change wait until this one was delivered and the last graph they see is current.
+    fn notify(&self, change: ExecutorChange, nodes: Option<Vec<Node>>, _order: MutexGuard<'_, ()>) -> anyhow::Result<()> {
+        let nodes = match nodes {
+            Some(nodes) => nodes,
+            None => return Ok(()),
+        };
+        let listeners = self
+            .inner
+            .listeners
+            .read()
+            .map_err(|_| anyhow::anyhow!("Shared executor listener lock was poisoned."))?;
+        for listener in listeners.iter() {
+            listener(&change, &nodes);
+        }
+        Ok(())
+    }
+
+    pub fn nodes(&self) -> anyhow::Result<Vec<Node>> {
+        self.read()?.executor.nodes()
+    }
+
+    pub fn nodes_for(&self, sender: &T) -> anyhow::Result<Vec<Node>> {
+        self.read()?.executor.nodes_for(sender)
+    }
+
+    pub fn execute_context(&self, context: CommandContext<T>) -> anyhow::Result<Option<anyhow::Result<()>>> {
+        let registrations = self.read()?;
+        let _executing = Executing::enter(self.address());
+        registrations.executor.execute_context(context)
+    }
+
+    pub fn suggest(&self, context: CommandContext<T>) -> anyhow::Result<Vec<String>> {
+        let registrations = self.read()?;
+        let _executing = Executing::enter(self.address());
+        registrations.executor.suggest(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{Command, NaturalCommand};
+    use crate::executor::context::CommandContext;
+    use crate::executor::shared::{ExecutorChange, SharedExecutor};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    pub fn test_register_unregister() -> anyhow::Result<()> {
+        let shared = SharedExecutor::<()>::default();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let listener_changes = Arc::clone(&changes);
+        shared.on_change(Box::new(move |change, nodes| {
+            listener_changes.lock().unwrap().push((change.clone(), nodes.len()));
+        }))?;
+
+        let token = shared.register("ping", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+        assert!(shared.register("ping", Command::Natural(NaturalCommand::default())).is_err());
+        assert!(shared.execute_context(CommandContext::create((), vec!["ping"]))?.is_some());
+
+        shared.unregister(token)?;
+        assert!(shared.execute_context(CommandContext::create((), vec!["ping"]))?.is_none());
+
+        let handle = shared.clone();
+        shared.register(
+            "reload",
+            Command::Natural(NaturalCommand::executable(Box::new(move |_| {
+                handle.register("pong", Command::Natural(NaturalCommand::default())).map(|_| ())
+            }))),
+        )?;
+        let reloaded = shared.execute_context(CommandContext::create((), vec!["reload"]))?.unwrap();
+        assert_eq!(reloaded.unwrap_err().to_string(), "Cannot change a shared executor from a command it is executing.");
+
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![
+                (ExecutorChange::Registered("ping".into()), 2),
+                (ExecutorChange::Unregistered("ping".into()), 1),
+                (ExecutorChange::Registered("reload".into()), 2),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use crate::arguments::chat::TextComponent;
+use crate::arguments::coordinates::{read_coordinates, Coordinates, PositionSource, WorldCoordinate};
+use crate::arguments::identifier::ResourceLocation;
+use crate::arguments::parse_with_reader;
+use crate::executor::command::{ParserFunction, Requirement};
+use crate::executor::context::{CommandContext, Value};
+use crate::executor::Executor;
+
+/// Optional interface for senders which live in a world, such as players, entities, command
+/// blocks or the server console.
+///
+/// Nothing in the executor requires it; implementing it unlocks permission requirements, parsers
+/// resolving coordinates against the sender and error reporting through `run_with_feedback`.
+pub trait CommandSource {
+    /// Shows a message to the sender, e.g. in chat or the server log.
+    fn send_feedback(&self, message: &TextComponent);
+
+    /// Shows an error to the sender; vanilla renders these in red.
+    fn send_failure(&self, message: &TextComponent) {
+        self.send_feedback(message)
+    }
+
+    /// Operator permission level, from 0 for regular players to 4 for the console.
+    fn permission_level(&self) -> u8 {
+        0
+    }
+
+    fn has_permission(&self, level: u8) -> bool {
+        self.permission_level() >= level
+    }
+
+    /// Position as x, y and z.
+    fn position(&self) -> (f64, f64, f64) {
+        (0.0, 0.0, 0.0)
+    }
+
+    /// Rotation as yaw (y rotation) and pitch (x rotation), in degrees.
+    fn rotation(&self) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    /// Dimension the sender is in, e.g. `minecraft:overworld`.
+    fn world(&self) -> Option<&ResourceLocation> {
+        None
+    }
+
+    /// UUID of the entity executing the command, `None` for the console or command blocks.
+    fn entity(&self) -> Option<u128> {
+        None
+    }
+}
+
+impl<S: CommandSource> PositionSource for S {
+    fn position(&self) -> (f64, f64, f64) {
+        CommandSource::position(self)
+    }
+
+    fn rotation(&self) -> (f32, f32) {
+        CommandSource::rotation(self)
+    }
+}
+
+/// Only lets senders with at least the permission level use a node.
+pub fn requires_permission<T: CommandSource>(level: u8) -> Requirement<T> {
+    Box::new(move |sender: &T| sender.has_permission(level))
+}
+
+/// Only lets senders backed by an entity use a node, e.g. for commands acting on the sender.
+pub fn requires_entity<T: CommandSource>() -> Requirement<T> {
+    Box::new(|sender: &T| sender.entity().is_some())
+}
+
+fn absolute(x: f64, y: f64, z: f64) -> Value {
+    Value::Coordinates(Coordinates::World { x: WorldCoordinate::new(false, x), y: WorldCoordinate::new(false, y), z: WorldCoordinate::new(false, z) })
+}
+
+/// Parses `minecraft:vec3` and resolves relative and local coordinates against the sender at
+/// parse time, so later redirects moving the sender don't change the result.
+///
+/// The position of the sender which parsed the input is baked into the value, so commands using
+/// this parser must not be executed from cached `ParseResults` for other senders or at a later
+/// time; use `vec3_parser` with `CommandContext::position_of` for those.
+pub fn resolved_vec3_parser<T: CommandSource>(center_correct: bool) -> ParserFunction<T> {
+    Box::new(move |context| {
+        let (coordinates, context) = parse_with_reader(context, |reader| read_coordinates(reader, false, center_correct))?;
+        let (x, y, z) = coordinates.position(context.sender());
+        Ok((absolute(x, y, z), context))
+    })
+}
+
+/// Parses `minecraft:block_pos` and resolves it to the absolute block against the sender.
+///
+/// Like `resolved_vec3_parser`, the result must not be executed from cached `ParseResults`.
+pub fn resolved_block_pos_parser<T: CommandSource>() -> ParserFunction<T> {
+    Box::new(|context| {
+        let (coordinates, context) = parse_with_reader(context, |reader| read_coordinates(reader, true, false))?;
+        let (x, y, z) = coordinates.block_position(context.sender());
+        Ok((absolute(f64::from(x), f64::from(y), f64::from(z)), context))
+    })
+}
+
+impl<T: CommandSource> CommandContext<T> {
+    pub fn send_feedback<S: Into<String>>(&self, message: S) {
+        self.sender().send_feedback(&TextComponent::text(message))
+    }
+
+    pub fn send_failure<S: Into<String>>(&self, message: S) {
+        self.sender().send_failure(&TextComponent::text(message))
+    }
+
+    /// Resolves the coordinates argument against the sender.
+    pub fn position_of(&self, identifier: &str) -> Option<(f64, f64, f64)> {
+        match self.value(identifier) {
+            Some(Value::Coordinates(coordinates)) => Some(coordinates.position(self.sender())),
+            _ => None,
+        }
+    }
+
+    /// Resolves the coordinates argument to a block position against the sender.
+    pub fn block_position_of(&self, identifier: &str) -> Option<(i32, i32, i32)> {
+        match self.value(identifier) {
+            Some(Value::Coordinates(coordinates)) => Some(coordinates.block_position(self.sender())),
+            _ => None,
+        }
+    }
+
+    /// Resolves the rotation argument against the sender.
+    pub fn rotation_of(&self, identifier: &str) -> Option<(f32, f32)> {
+        match self.value(identifier) {
+            Some(Value::Rotation(rotation)) => Some(rotation.resolve(self.sender())),
+            _ => None,
+        }
+    }
+}
+
+impl<T: CommandSource + Clone> Executor<T> {
+    /// Runs raw input for the sender and reports a failure, including unknown commands and their
+    /// suggestions, back to the sender.
+    pub fn run_with_feedback(&self, sender: T, input: &str) -> anyhow::Result<usize> {
+        let result = self.execute_count(CommandContext::from_input(sender.clone(), input));
+        if let Err(error) = &result {
+            sender.send_failure(&TextComponent::text(error.to_string()));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::chat::TextComponent;
+    use crate::arguments::coordinates::vec3_parser;
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand, ParserFunction};
+    use crate::executor::context::CommandContext;
+    use crate::executor::source::{requires_permission, resolved_vec3_parser, CommandSource};
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::protocol::Parser;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    struct Player {
+        level: u8,
+        position: (f64, f64, f64),
+        chat: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl CommandSource for Player {
+        fn send_feedback(&self, message: &TextComponent) {
+            self.chat.lock().unwrap().push(message.plain_text());
+        }
+
+        fn permission_level(&self) -> u8 {
+            self.level
+        }
+
+        fn position(&self) -> (f64, f64, f64) {
+            self.position
+        }
+    }
+
+    fn destination(parser: ParserFunction<Player>) -> Command<Player> {
+        Command::ArgParser(ArgParserCommand::executable(
+            Box::new(|context: CommandContext<Player>| {
+                let (x, y, z) = context.position_of("destination").expect("the destination should be parsed");
+                context.send_feedback(format!("Teleported to {} {} {}", x, y, z));
+                Ok(())
+            }),
+            "destination".into(),
+            Parser::Vec3,
+            parser,
+        ))
+    }
+
+    #[test]
+    pub fn test_command_source() -> anyhow::Result<()> {
+        let mut tp = Command::Natural(NaturalCommand::default().with_requirement(requires_permission(2)));
+        tp.child("destination", destination(resolved_vec3_parser(false)))?;
+        let mut spawn = Command::Natural(NaturalCommand::default());
+        spawn.child("destination", destination(vec3_parser(false)))?;
+        let mut executor = Executor::new();
+        executor.child("tp", tp)?;
+        executor.child("spawn", spawn)?;
+
+        let chat = Arc::new(Mutex::new(Vec::new()));
+        let op = Player { level: 2, position: (10.0, 64.0, -5.0), chat: chat.clone() };
+        let player = Player { level: 0, ..op.clone() };
+        executor.run_with_feedback(op.clone(), "tp ~ ~1 ~")?;
+        executor.run_with_feedback(player.clone(), "spawn 0 ~ 0")?;
+        assert!(executor.run_with_feedback(player.clone(), "tp 0 0 0").is_err());
+        assert!(executor.run_with_feedback(player.clone(), "spwan 0 0 0").is_err());
+        assert_eq!(*chat.lock().unwrap(), vec!["Teleported to 10 65 -5", "Teleported to 0 64 0", "Unknown command.", "Unknown command. Did you mean /spawn?"]);
+
+        assert_eq!(executor.suggest(CommandContext::from_input(player, "t"))?, Vec::<String>::new());
+        assert_eq!(executor.suggest(CommandContext::from_input(op, "t"))?, vec!["tp"]);
+        Ok(())
+    }
+}
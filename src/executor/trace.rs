@@ -0,0 +1,164 @@
+use crate::executor::command::{Command, Next};
+use crate::executor::graph;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    Literal { token: String, literal: String },
+    UnknownLiteral { token: String },
+    Parsed { identifier: String, parser: &'static str, consumed: Vec<String> },
+    ParserFailed { identifier: String, parser: &'static str, reason: String },
+    Executed { remaining: Vec<String> },
+    NotExecutable { remaining: Vec<String> },
+    Redirected { target: Vec<String>, senders: usize },
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceEvent::Literal { token, literal } => write!(f, "matched literal {} from {:?}", literal, token),
+            TraceEvent::UnknownLiteral { token } => write!(f, "no literal matched {:?}", token),
+            TraceEvent::Parsed { identifier, parser, consumed } => {
+                write!(f, "parsed <{}: {}> from {:?}", identifier, parser, consumed)
+            }
+            TraceEvent::ParserFailed { identifier, parser, reason } => {
+                write!(f, "failed to parse <{}: {}>: {}", identifier, parser, reason)
+            }
+            TraceEvent::Executed { remaining } => write!(f, "executed with remaining {:?}", remaining),
+            TraceEvent::NotExecutable { remaining } => {
+                write!(f, "stopped at a non executable node with remaining {:?}", remaining)
+            }
+            TraceEvent::Redirected { target, senders } => {
+                write!(f, "redirected to <root>{} for {} sender(s)", target.iter().map(|segment| format!(" {}", segment)).collect::<String>(), senders)
+            }
+        }
+    }
+}
+
+/// Ordered record of every step taken while walking the tree for a single execution.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionTrace {
+    events: Vec<TraceEvent>,
+}
+
+impl ExecutionTrace {
+    pub fn push(&mut self, event: TraceEvent) {
+        self.events.push(event);
+    }
+
+    pub fn events(&self) -> &Vec<TraceEvent> {
+        &self.events
+    }
+
+    pub fn into_inner(self) -> Vec<TraceEvent> {
+        self.events
+    }
+}
+
+impl Display for ExecutionTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (step, event) in self.events.iter().enumerate() {
+            writeln!(f, "{}: {}", step, event)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders a command tree as an indented ASCII tree, with literals sorted at every level.
+pub fn render_tree<T>(root: &Next<T>) -> String {
+    let mut rendered = String::from("<root>\n");
+    render_children(root, "", &mut rendered);
+    rendered
+}
+
+fn render_children<T>(next: &Next<T>, indent: &str, rendered: &mut String) {
+    let children = match next {
+        Next::ArgumentParser(command) => vec![(None, command)],
+        Next::LiteralMap(map) => graph::sorted_literals(map).into_iter().map(|(literal, command)| (Some(literal), command)).collect(),
+    };
+    let count = children.len();
+    for (index, (literal, command)) in children.into_iter().enumerate() {
+        let last = index + 1 == count;
+        rendered.push_str(indent);
+        rendered.push_str(if last { "`-- " } else { "|-- " });
+        match (literal, command) {
+            (_, Command::ArgParser(inner)) => {
+                rendered.push_str(&format!("<{}: {}>", inner.identifier(), inner.parser().identifier()));
+            }
+            (Some(literal), Command::Natural(_)) => rendered.push_str(literal),
+            (None, Command::Natural(_)) => unreachable!("Natural commands are always bound through a literal map."),
+        }
+        if command.is_executable() {
+            rendered.push_str(" (executable)");
+        }
+        rendered.push('\n');
+        let child_indent = format!("{}{}", indent, if last { "    " } else { "|   " });
+        render_children(command.next(), &child_indent, rendered);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand};
+    use crate::executor::context::{CommandContext, Value};
+    use crate::executor::trace::TraceEvent;
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::protocol::Parser;
+
+    fn executor() -> anyhow::Result<Executor<()>> {
+        let mut amount = Command::ArgParser(ArgParserCommand::executable(
+            Box::new(|_| Ok(())),
+            "amount".into(),
+            Parser::Integer { bits: 0u8.into(), min: None, max: None },
+            Box::new(|mut context| {
+                let value = match context.args().first() {
+                    Some(arg) => Value::Integer(arg.parse()?),
+                    None => anyhow::bail!("Expected an integer."),
+                };
+                context.trim_top();
+                Ok((value, context))
+            }),
+        ));
+        amount.child("silent", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+        let mut give = Command::Natural(NaturalCommand::default());
+        give.child("amount", amount)?;
+        let mut executor = Executor::new();
+        executor.child("give", give)?;
+        executor.child("clear", Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(())))))?;
+        Ok(executor)
+    }
+
+    #[test]
+    pub fn test_trace_events() -> anyhow::Result<()> {
+        let executor = executor()?;
+        let (result, trace) = executor.execute_traced(CommandContext::create((), vec!["give", "5", "silent"]));
+        assert!(result?.is_some());
+        assert_eq!(
+            *trace.events(),
+            vec![
+                TraceEvent::Literal { token: "give".into(), literal: "give".into() },
+                TraceEvent::Parsed { identifier: "amount".into(), parser: "brigadier:integer", consumed: vec!["5".into()] },
+                TraceEvent::Literal { token: "silent".into(), literal: "silent".into() },
+                TraceEvent::Executed { remaining: Vec::new() },
+            ]
+        );
+
+        let (result, trace) = executor.execute_traced(CommandContext::create((), vec!["give", "five"]));
+        assert!(result.is_err());
+        assert!(matches!(trace.events().last(), Some(TraceEvent::ParserFailed { .. })));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_render_tree() -> anyhow::Result<()> {
+        assert_eq!(
+            executor()?.render_tree(),
+            "<root>\n\
+             |-- clear (executable)\n\
+             `-- give\n    \
+             `-- <amount: brigadier:integer> (executable)\n        \
+             `-- silent (executable)\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::executor::command::Dispatch;
+use crate::executor::context::Args;
+use std::fmt::{Display, Formatter};
+
+/// Last token of a dispatch which didn't match any literal the sender can use, with the usable
+/// literals close to it.
+pub(crate) struct Miss {
+    pub(crate) args: Args,
+    pub(crate) suggestions: Vec<String>,
+}
+
+/// Error for input which didn't reach any command function, with the literals the mistyped token
+/// was probably meant to be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCommandError {
+    input: String,
+    consumed: String,
+    token: Option<String>,
+    suggestions: Vec<String>,
+}
+
+impl UnknownCommandError {
+    pub(crate) fn new<T>(dispatch: &Dispatch<'_, T>, input: &str) -> Self {
+        match dispatch.miss() {
+            Some(miss) => Self {
+                input: input.to_string(),
+                consumed: miss.args.consumed().to_string(),
+                token: miss.args.first().map(String::from),
+                suggestions: miss.suggestions.clone(),
+            },
+            None => Self { input: input.to_string(), consumed: input.to_string(), token: None, suggestions: Vec::new() },
+        }
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// The token which didn't match any literal, or `None` if the input ended before reaching an
+    /// executable node.
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Whether the unknown token is the command itself rather than one of its arguments.
+    pub fn is_root(&self) -> bool {
+        self.token.is_some() && self.consumed.is_empty()
+    }
+
+    /// Literals close to the unknown token, closest first.
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+
+    /// The input up to and including the unknown token, with the token replaced by each suggestion.
+    pub fn corrections(&self) -> Vec<String> {
+        self.suggestions
+            .iter()
+            .map(|suggestion| match self.consumed.is_empty() {
+                true => format!("/{}", suggestion),
+                false => format!("/{} {}", self.consumed, suggestion),
+            })
+            .collect()
+    }
+}
+
+impl Display for UnknownCommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.token, self.is_root()) {
+            (None, _) => return write!(f, "Unknown or incomplete command."),
+            (Some(_), true) => write!(f, "Unknown command.")?,
+            (Some(_), false) => write!(f, "Incorrect argument for command.")?,
+        }
+        match self.corrections().as_slice() {
+            [] => Ok(()),
+            [correction] => write!(f, " Did you mean {}?", correction),
+            [corrections @ .., last] => write!(f, " Did you mean {} or {}?", corrections.join(", "), last),
+        }
+    }
+}
+
+impl std::error::Error for UnknownCommandError {}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{Command, NaturalCommand};
+    use crate::executor::unknown::UnknownCommandError;
+    use crate::executor::{CommandChildContainer, Executor};
+
+    fn executable() -> Command<()> {
+        Command::Natural(NaturalCommand::executable(Box::new(|_| Ok(()))))
+    }
+
+    #[test]
+    pub fn test_did_you_mean() -> anyhow::Result<()> {
+        let mut gamemode = Command::Natural(NaturalCommand::default());
+        gamemode.child("creative", executable())?;
+        gamemode.child("survival", executable())?;
+        let mut executor = Executor::new();
+        executor.child("gamemode", gamemode)?;
+        executor.child("gamerule", executable())?;
+        executor.child("give", executable())?;
+
+        let error = executor.run_input((), "gamemod").unwrap_err();
+        let unknown = error.downcast_ref::<UnknownCommandError>().expect("the error should be structured");
+        assert_eq!((unknown.token(), unknown.is_root()), (Some("gamemod"), true));
+        assert_eq!(error.to_string(), "Unknown command. Did you mean /gamemode?");
+        assert_eq!(executor.run_input((), "gamemode creatvie").unwrap_err().to_string(), "Incorrect argument for command. Did you mean /gamemode creative?");
+        assert_eq!(executor.run_input((), "gamerul").unwrap_err().to_string(), "Unknown command. Did you mean /gamerule?");
+        assert_eq!(executor.run_input((), "gamemule").unwrap_err().to_string(), "Unknown command. Did you mean /gamerule or /gamemode?");
+        assert_eq!(executor.run_input((), "teleport").unwrap_err().to_string(), "Unknown command.");
+        assert_eq!(executor.run_input((), "gamemode").unwrap_err().to_string(), "Unknown or incomplete command.");
+        executor.run_input((), "give")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,463 @@
+use crate::protocol::{BrigadierFlags, EntitySelector, MinMax, Node, NodeName, Parser, ScoreHolderSelector, StringDescription};
+use minecraft_data_types::nums::VarInt;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+fn node_at<'a>(nodes: &'a [Node], index: &VarInt) -> anyhow::Result<(usize, &'a Node)> {
+    let index = usize::try_from(**index)?;
+    match nodes.get(index) {
+        Some(node) => Ok((index, node)),
+        None => anyhow::bail!("Node index {} is out of bounds.", index),
+    }
+}
+
+fn node_name(node: &Node) -> anyhow::Result<String> {
+    match node.name() {
+        Some(name) => Ok(name.string().clone()),
+        None => anyhow::bail!("Non root nodes should be named."),
+    }
+}
+
+/// Resolves the name path from the root to every node reachable through children.
+fn node_paths(nodes: &[Node]) -> anyhow::Result<Vec<Option<Vec<String>>>> {
+    let mut paths = vec![None; nodes.len()];
+    if nodes.is_empty() {
+        return Ok(paths);
+    }
+    paths[0] = Some(Vec::new());
+    let mut queue = vec![0];
+    while let Some(parent) = queue.pop() {
+        let parent_path = paths[parent].clone().expect("Queued nodes always have a path.");
+        for child in nodes[parent].children() {
+            let (index, node) = node_at(nodes, child)?;
+            if paths[index].is_none() {
+                paths[index] = Some([parent_path.clone(), vec![node_name(node)?]].concat());
+                queue.push(index);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+fn parser_properties(parser: &Parser) -> Option<Value> {
+    let mut properties = Map::new();
+    match parser {
+        Parser::Double { min, max, .. } => {
+            if let Some(min) = min {
+                properties.insert("min".into(), (*min).into());
+            }
+            if let Some(max) = max {
+                properties.insert("max".into(), (*max).into());
+            }
+        }
+        Parser::Float { min, max, .. } => {
+            if let Some(min) = min {
+                properties.insert("min".into(), (*min).into());
+            }
+            if let Some(max) = max {
+                properties.insert("max".into(), (*max).into());
+            }
+        }
+        Parser::Integer { min, max, .. } => {
+            if let Some(min) = min {
+                properties.insert("min".into(), (*min).into());
+            }
+            if let Some(max) = max {
+                properties.insert("max".into(), (*max).into());
+            }
+        }
+        Parser::Long { min, max, .. } => {
+            if let Some(min) = min {
+                properties.insert("min".into(), (*min).into());
+            }
+            if let Some(max) = max {
+                properties.insert("max".into(), (*max).into());
+            }
+        }
+        Parser::String { info } => {
+            let kind = match info {
+                StringDescription::SingleWord => "word",
+                StringDescription::QuotablePhrase => "phrase",
+                StringDescription::GreedyPhrase => "greedy",
+            };
+            properties.insert("type".into(), kind.into());
+        }
+        Parser::Entity { selector } => {
+            properties.insert("type".into(), if selector.is_players_only() { "players" } else { "entities" }.into());
+            properties.insert("amount".into(), if selector.is_single() { "single" } else { "multiple" }.into());
+        }
+        Parser::ScoreHolder { selector } => {
+            properties.insert("amount".into(), if selector.is_multiple() { "multiple" } else { "single" }.into());
+        }
+        Parser::Range { decimals } => {
+            properties.insert("decimals".into(), (*decimals).into());
+        }
+        _ => return None,
+    }
+    if properties.is_empty() {
+        None
+    } else {
+        Some(Value::Object(properties))
+    }
+}
+
+fn node_json(nodes: &[Node], index: usize, paths: &[Option<Vec<String>>], depth: usize) -> anyhow::Result<Value> {
+    if depth > nodes.len() {
+        anyhow::bail!("Node graph contains a child cycle.");
+    }
+    let node = &nodes[index];
+    let flags = node.flags();
+    let mut object = Map::new();
+    let kind = if flags.is_root() {
+        "root"
+    } else if flags.is_literal() {
+        "literal"
+    } else {
+        "argument"
+    };
+    object.insert("type".into(), kind.into());
+    if flags.is_argument() {
+        let parser = match node.parser() {
+            Some(parser) => parser,
+            None => anyhow::bail!("Argument nodes should have a parser."),
+        };
+        object.insert("parser".into(), parser.identifier().into());
+        if let Some(properties) = parser_properties(parser) {
+            object.insert("properties".into(), properties);
+        }
+    }
+    let mut children = Map::new();
+    for child in node.children() {
+        let (child_index, child_node) = node_at(nodes, child)?;
+        children.insert(node_name(child_node)?, node_json(nodes, child_index, paths, depth + 1)?);
+    }
+    if !children.is_empty() {
+        object.insert("children".into(), Value::Object(children));
+    }
+    if flags.is_executable() {
+        object.insert("executable".into(), true.into());
+    }
+    if flags.has_redirect() {
+        let redirect = match node.redirect_node() {
+            Some(redirect) => node_at(nodes, redirect)?.0,
+            None => anyhow::bail!("Redirect node should be provided if the flags are set as such."),
+        };
+        match &paths[redirect] {
+            Some(path) => object.insert("redirect".into(), path.clone().into()),
+            None => anyhow::bail!("Redirect target {} is not reachable from the root.", redirect),
+        };
+    }
+    Ok(Value::Object(object))
+}
+
+/// Exports a node graph in the format of the vanilla `commands.json` data generator report.
+pub fn to_commands_json(nodes: &[Node]) -> anyhow::Result<Value> {
+    if nodes.is_empty() {
+        anyhow::bail!("Node graph should contain a root node.");
+    }
+    let paths = node_paths(nodes)?;
+    node_json(nodes, 0, &paths, 0)
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Exports a node graph as a Graphviz DOT digraph.
+///
+/// Literals are boxes, arguments are ellipses, executable nodes get a double border and redirects
+/// are dashed edges.
+pub fn to_dot(nodes: &[Node]) -> anyhow::Result<String> {
+    let mut dot = String::from("digraph commands {\n");
+    for (index, node) in nodes.iter().enumerate() {
+        let flags = node.flags();
+        let (label, shape) = if flags.is_root() {
+            ("<root>".to_string(), "diamond")
+        } else if flags.is_literal() {
+            (node_name(node)?, "box")
+        } else {
+            let parser = match node.parser() {
+                Some(parser) => parser,
+                None => anyhow::bail!("Argument nodes should have a parser."),
+            };
+            let mut label = format!("<{}: {}>", node_name(node)?, parser.identifier());
+            if let Some(properties) = parser_properties(parser) {
+                label.push_str(&format!("\n{}", properties));
+            }
+            (label, "ellipse")
+        };
+        dot.push_str(&format!(
+            "    n{} [label=\"{}\", shape={}{}];\n",
+            index,
+            escape(&label),
+            shape,
+            if flags.is_executable() { ", peripheries=2" } else { "" }
+        ));
+        for child in node.children() {
+            dot.push_str(&format!("    n{} -> n{};\n", index, node_at(nodes, child)?.0));
+        }
+        if let (true, Some(redirect)) = (flags.has_redirect(), node.redirect_node()) {
+            dot.push_str(&format!("    n{} -> n{} [style=dashed, label=\"redirect\"];\n", index, node_at(nodes, redirect)?.0));
+        }
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
+
+fn property<'a>(properties: Option<&'a Value>, key: &str) -> Option<&'a Value> {
+    properties.and_then(|properties| properties.get(key))
+}
+
+fn property_str<'a>(properties: Option<&'a Value>, key: &str) -> anyhow::Result<Option<&'a str>> {
+    match property(properties, key) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(other) => anyhow::bail!("Expected property {} to be a string, found {}.", key, other),
+    }
+}
+
+fn property_f64(properties: Option<&Value>, key: &str) -> anyhow::Result<Option<f64>> {
+    match property(properties, key) {
+        None => Ok(None),
+        Some(value) => match value.as_f64() {
+            Some(value) => Ok(Some(value)),
+            None => anyhow::bail!("Expected property {} to be a number, found {}.", key, value),
+        },
+    }
+}
+
+fn property_i64(properties: Option<&Value>, key: &str) -> anyhow::Result<Option<i64>> {
+    match property(properties, key) {
+        None => Ok(None),
+        Some(value) => match value.as_i64() {
+            Some(value) => Ok(Some(value)),
+            None => anyhow::bail!("Expected property {} to be an integer, found {}.", key, value),
+        },
+    }
+}
+
+fn parser_from_json(identifier: &str, properties: Option<&Value>) -> anyhow::Result<Parser> {
+    let min_max = |min: bool, max: bool| MinMax::new(min, max);
+    Ok(match identifier {
+        "brigadier:double" => {
+            let (min, max) = (property_f64(properties, "min")?, property_f64(properties, "max")?);
+            Parser::Double { bits: min_max(min.is_some(), max.is_some()), min, max }
+        }
+        "brigadier:float" => {
+            let (min, max) = (property_f64(properties, "min")?, property_f64(properties, "max")?);
+            Parser::Float {
+                bits: min_max(min.is_some(), max.is_some()),
+                min: min.map(|min| min as f32),
+                max: max.map(|max| max as f32),
+            }
+        }
+        "brigadier:integer" => {
+            let min = property_i64(properties, "min")?.map(i32::try_from).transpose()?;
+            let max = property_i64(properties, "max")?.map(i32::try_from).transpose()?;
+            Parser::Integer { bits: min_max(min.is_some(), max.is_some()), min, max }
+        }
+        "brigadier:long" => {
+            let (min, max) = (property_i64(properties, "min")?, property_i64(properties, "max")?);
+            Parser::Long { bits: min_max(min.is_some(), max.is_some()), min, max }
+        }
+        "brigadier:string" => Parser::String {
+            info: match property_str(properties, "type")? {
+                Some("word") => StringDescription::SingleWord,
+                Some("phrase") => StringDescription::QuotablePhrase,
+                Some("greedy") => StringDescription::GreedyPhrase,
+                other => anyhow::bail!("Unknown string type {:?}.", other),
+            },
+        },
+        "minecraft:entity" => Parser::Entity {
+            selector: EntitySelector::new(
+                property_str(properties, "amount")? == Some("single"),
+                property_str(properties, "type")? == Some("players"),
+            ),
+        },
+        "minecraft:score_holder" => Parser::ScoreHolder {
+            selector: ScoreHolderSelector::new(property_str(properties, "amount")? == Some("multiple")),
+        },
+        "minecraft:range" => Parser::Range {
+            decimals: property(properties, "decimals").and_then(Value::as_bool).unwrap_or(false),
+        },
+        identifier => match Parser::from_identifier(identifier) {
+            Some(parser) => parser,
+            None => anyhow::bail!("Unknown parser identifier {}.", identifier),
+        },
+    })
+}
+
+struct ImportedNode {
+    kind: String,
+    name: Option<String>,
+    parser: Option<Parser>,
+    executable: bool,
+    children: Vec<usize>,
+    redirect: Option<Vec<String>>,
+}
+
+fn import_node(
+    json: &Value,
+    name: Option<String>,
+    path: Vec<String>,
+    imported: &mut Vec<Option<ImportedNode>>,
+    paths: &mut HashMap<Vec<String>, usize>,
+) -> anyhow::Result<usize> {
+    let index = imported.len();
+    imported.push(None);
+    paths.insert(path.clone(), index);
+
+    let kind = match json.get("type").and_then(Value::as_str) {
+        Some(kind @ ("root" | "literal" | "argument")) => kind.to_string(),
+        other => anyhow::bail!("Unknown node type {:?} at {:?}.", other, path),
+    };
+    if (kind == "root") != name.is_none() {
+        anyhow::bail!("Only the top level node may be the root, found {} at {:?}.", kind, path);
+    }
+    let parser = if kind == "argument" {
+        match json.get("parser").and_then(Value::as_str) {
+            Some(identifier) => Some(parser_from_json(identifier, json.get("properties"))?),
+            None => anyhow::bail!("Argument node at {:?} has no parser.", path),
+        }
+    } else {
+        None
+    };
+    let redirect = match json.get("redirect") {
+        None => None,
+        Some(Value::Array(segments)) => Some(
+            segments
+                .iter()
+                .map(|segment| match segment.as_str() {
+                    Some(segment) => Ok(segment.to_string()),
+                    None => anyhow::bail!("Redirect paths should only contain strings."),
+                })
+                .collect::<anyhow::Result<Vec<String>>>()?,
+        ),
+        Some(other) => anyhow::bail!("Expected redirect to be a path, found {}.", other),
+    };
+
+    let mut children = Vec::new();
+    if let Some(json_children) = json.get("children") {
+        let json_children = match json_children.as_object() {
+            Some(json_children) => json_children,
+            None => anyhow::bail!("Expected children of {:?} to be an object.", path),
+        };
+        for (child_name, child) in json_children {
+            let child_path = [path.clone(), vec![child_name.clone()]].concat();
+            children.push(import_node(child, Some(child_name.clone()), child_path, imported, paths)?);
+        }
+    }
+
+    imported[index] = Some(ImportedNode {
+        kind,
+        name,
+        parser,
+        executable: json.get("executable").and_then(Value::as_bool).unwrap_or(false),
+        children,
+        redirect,
+    });
+    Ok(index)
+}
+
+/// Imports a vanilla `commands.json` report into a node graph with the root at index 0.
+pub fn from_commands_json(json: &Value) -> anyhow::Result<Vec<Node>> {
+    let mut imported = Vec::new();
+    let mut paths = HashMap::new();
+    import_node(json, None, Vec::new(), &mut imported, &mut paths)?;
+
+    imported
+        .into_iter()
+        .map(|node| {
+            let node = node.expect("Every reserved node index should be filled.");
+            let redirect = match &node.redirect {
+                Some(path) => match paths.get(path) {
+                    Some(target) => Some(VarInt::try_from(*target)?),
+                    None => anyhow::bail!("Redirect target {:?} does not exist.", path),
+                },
+                None => None,
+            };
+            let children = node.children.into_iter().map(VarInt::try_from).collect::<Result<Vec<VarInt>, _>>()?;
+            Ok(Node::new(
+                BrigadierFlags::new(
+                    node.kind == "literal",
+                    node.kind == "argument",
+                    node.executable,
+                    redirect.is_some(),
+                    false,
+                ),
+                (VarInt::try_from(children.len())?, children),
+                redirect,
+                node.name.as_deref().map(NodeName::from),
+                node.parser,
+                None,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::executor::command::{ArgParserCommand, Command, NaturalCommand};
+    use crate::executor::context::Value;
+    use crate::executor::matching::LiteralMatching;
+    use crate::executor::{CommandChildContainer, Executor};
+    use crate::export::{from_commands_json, to_commands_json, to_dot};
+    use crate::protocol::{MinMax, Parser};
+
+    #[test]
+    pub fn test_commands_json_round_trip() -> anyhow::Result<()> {
+        let mut experience = Command::Natural(NaturalCommand::default());
+        experience.child(
+            "amount",
+            Command::ArgParser(ArgParserCommand::executable(
+                Box::new(|_| Ok(())),
+                "amount".into(),
+                Parser::Integer { bits: MinMax::new(true, false), min: Some(0), max: None },
+                Box::new(|context| Ok((Value::Integer(0), context))),
+            )),
+        )?;
+        let mut executor = Executor::<()>::new().with_matching(LiteralMatching::exact().namespace("minecraft"));
+        executor.child("experience", experience)?;
+
+        let json = executor.to_commands_json()?;
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "root",
+                "children": {
+                    "experience": {
+                        "type": "literal",
+                        "children": {
+                            "amount": {
+                                "type": "argument",
+                                "parser": "brigadier:integer",
+                                "properties": { "min": 0 },
+                                "executable": true
+                            }
+                        }
+                    },
+                    "minecraft:experience": {
+                        "type": "literal",
+                        "redirect": ["experience"]
+                    }
+                }
+            })
+        );
+
+        let imported = from_commands_json(&json)?;
+        assert_eq!(imported.len(), 4);
+        assert_eq!(to_commands_json(&imported)?, json);
+        assert!(to_dot(&imported)?.contains("[style=dashed, label=\"redirect\"]"));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_import_rejects_unknown_parser() {
+        let json = serde_json::json!({
+            "type": "root",
+            "children": { "a": { "type": "argument", "parser": "minecraft:unknown" } }
+        });
+        assert!(from_commands_json(&json).is_err());
+    }
+}
@@ -2,3 +2,5 @@
 
 pub mod protocol;
 pub mod executor;
+pub mod export;
+pub mod arguments;
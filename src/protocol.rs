@@ -8,7 +8,7 @@ use tokio::io::AsyncWrite;
 macro_rules! strict_enum {
     ($($enum_name:ident; $index_type:ty { $($byte_representation:literal => $option_name:ident;)* })*) => {
         $(
-            #[derive(Debug)]
+            #[derive(Debug, Clone)]
             pub enum $enum_name {
                 $($option_name,)*
             }
@@ -137,6 +137,12 @@ macro_rules! bit_map {
 }
 
 macro_rules! parser {
+    (@unit $enum_identifier:ident) => {
+        Some(Parser::$enum_identifier)
+    };
+    (@unit $enum_identifier:ident $($properties:tt)+) => {
+        None
+    };
     ($(
         $identifier:literal as $enum_identifier:ident $(
             => BitProperties: $bit_field:ty $(
@@ -148,7 +154,7 @@ macro_rules! parser {
             )+
         )?;
     )+) => {
-        #[derive(Debug)]
+        #[derive(Debug, Clone)]
         pub enum Parser {
             $(
                 $enum_identifier$(
@@ -163,6 +169,26 @@ macro_rules! parser {
             )+
         }
 
+        impl Parser {
+            pub fn identifier(&self) -> &'static str {
+                match self {
+                    $(
+                        Parser::$enum_identifier { .. } => $identifier,
+                    )+
+                }
+            }
+
+            /// Creates the parser for an identifier, if that parser does not carry any properties.
+            pub fn from_identifier(identifier: &str) -> Option<Parser> {
+                match identifier {
+                    $(
+                        $identifier => parser!(@unit $enum_identifier $($bit_field)? $($($property_ident)+)?),
+                    )+
+                    _ => None,
+                }
+            }
+        }
+
         impl Encodable for Parser {
             fn encode<W: std::io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
                 match self {
@@ -331,6 +357,34 @@ impl BrigadierFlags {
     pub fn is_argument(&self) -> bool {
         self.node_argument
     }
+
+    pub fn is_executable(&self) -> bool {
+        self.executable
+    }
+
+    pub fn has_redirect(&self) -> bool {
+        self.has_redirect
+    }
+
+    pub fn has_suggestions_type(&self) -> bool {
+        self.has_suggestions_type
+    }
+}
+
+impl EntitySelector {
+    pub fn is_single(&self) -> bool {
+        self.entity_or_player
+    }
+
+    pub fn is_players_only(&self) -> bool {
+        self.players_only
+    }
+}
+
+impl ScoreHolderSelector {
+    pub fn is_multiple(&self) -> bool {
+        self.multiple
+    }
 }
 
 parser! {
@@ -412,6 +466,30 @@ impl Node {
             suggestions_type,
         }
     }
+
+    pub fn flags(&self) -> BrigadierFlags {
+        self.flags
+    }
+
+    pub fn children(&self) -> &Vec<VarInt> {
+        &self.children.1
+    }
+
+    pub fn redirect_node(&self) -> Option<&VarInt> {
+        self.redirect_node.as_ref()
+    }
+
+    pub fn name(&self) -> Option<&NodeName> {
+        self.name.as_ref()
+    }
+
+    pub fn parser(&self) -> Option<&Parser> {
+        self.parser.as_ref()
+    }
+
+    pub fn suggestions_type(&self) -> Option<&SuggestionsType> {
+        self.suggestions_type.as_ref()
+    }
 }
 
 impl Encodable for Node {